@@ -1,11 +1,33 @@
-use crate::flag::Flag;
-use clap::{Parser, Subcommand};
-use std::num::{IntErrorKind, NonZero};
+use crate::{
+    color::ColorMode,
+    flag::Flag,
+    paint::Direction,
+    palette,
+    render::transform::{Mirror, Rotation, Transform},
+    slideshow::Transition,
+    theme::Target,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::{
+    num::{IntErrorKind, NonZero},
+    path::PathBuf,
+    time::Duration,
+};
 
 #[derive(Subcommand, Clone)]
 pub enum Command {
     /// Print a pride flag
     Print(PrintCli),
+    /// Show one flag after another in fullscreen
+    Slideshow(SlideshowCli),
+    /// Color text from stdin with the stripes of a flag
+    Paint(PaintCli),
+    /// Print text in big letters filled with a flag
+    Banner(BannerCli),
+    /// Generate a terminal color scheme from the colors of a flag
+    Theme(ThemeCli),
+    /// Print the exact colors of a flag for design tools and stylesheets
+    Palette(PaletteCli),
     #[command(alias = "ls", alias = "l")]
     /// List all available flags
     List {
@@ -27,19 +49,65 @@ pub struct PrintCli {
     /// The name (or alias) of the flag to output
     #[arg(value_enum)]
     pub flag: Flag,
+    #[command(flatten)]
+    pub shape: ShapeArgs,
+    /// The output format, defaults to an image if the terminal supports it and to ansi otherwise
+    #[arg(short, long, value_enum)]
+    pub format: Option<Format>,
+    #[arg(
+        long,
+        value_parser = parse_resolution,
+        help = "The resolution of raster images",
+        long_help = "The resolution of raster images in pixels, e.g. 1920x1080. Defaults to 10x20 pixels per charachter"
+    )]
+    pub pixels: Option<Resolution>,
+    /// Write the flag to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    #[command(flatten)]
+    pub color: ColorArgs,
+    /// Animate the flag until Ctrl-C is pressed
+    #[arg(long, value_enum)]
+    pub animate: Option<Animation>,
+    /// The frames per second of the animation
+    #[arg(long, default_value_t = const { unsafe { NonZero::new_unchecked(20) }})]
+    pub fps: NonZero<u32>,
+    /// Stop the animation after this many seconds
+    #[arg(long, value_parser = parse_duration, requires = "animate")]
+    pub duration: Option<Duration>,
+    /// Show the flag in fullscreen and redraw it whenever the terminal is resized
+    #[arg(long, alias = "fullscreen", conflicts_with_all = ["animate", "output"])]
+    pub watch: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Rotation::None,
+        conflicts_with = "animate",
+        help = "Turn the flag clockwise by degrees",
+        long_help = "Turn the flag clockwise by degrees. In the terminal the flag still fills the given size, images are turned as a whole"
+    )]
+    pub rotate: Rotation,
+    /// Flip the flag after turning it
+    #[arg(long, value_enum, conflicts_with = "animate")]
+    pub mirror: Option<Mirror>,
+}
+
+/// The size of the flag and the slope of its triangle, shared by the commands drawing flags
+#[derive(Args, Clone)]
+pub struct ShapeArgs {
     #[arg(
         short,
-		long,
-		value_parser = parse_width,
-		help = "The width of the flag",
-		long_help = "The width of the flag, either an absolute length (charachters) or percentages of the terminal width"
+        long,
+        value_parser = parse_width,
+        help = "The width of the flag",
+        long_help = "The width of the flag, either an absolute length (charachters) or percentages of the terminal width"
     )]
     pub width: Option<Size>,
     #[arg(
         long,
         value_parser = parse_width,
-		help = "The height of the flag",
-		long_help = "The height of the flag, either an absolute length (charachters) or percentages of the terminal height"
+        help = "The height of the flag",
+        long_help = "The height of the flag, either an absolute length (charachters) or percentages of the terminal height"
     )]
     pub height: Option<Size>,
     /// The slope of the triangle on the side present on some flags
@@ -47,6 +115,181 @@ pub struct PrintCli {
     pub slope: NonZero<usize>,
 }
 
+#[derive(Args, Clone)]
+pub struct ColorArgs {
+    #[arg(
+        long = "color",
+        value_name = "COLOR",
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help = "Which colors to use",
+        long_help = "Which colors to use, auto detects them from the terminal and respects NO_COLOR and CLICOLOR_FORCE"
+    )]
+    pub choice: ColorChoice,
+}
+
+impl ColorArgs {
+    pub fn mode(&self, is_terminal: bool) -> ColorMode {
+        self.choice.mode(is_terminal)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Animation {
+    /// The columns move up and down like a flag in the wind
+    Wave,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    /// 24-bit colors
+    #[value(alias = "24bit")]
+    Truecolor,
+    /// The xterm 256 color palette
+    #[value(name = "256")]
+    Xterm256,
+    /// The basic ANSI colors
+    #[value(name = "16")]
+    Ansi,
+    /// No colors at all
+    None,
+}
+
+impl ColorChoice {
+    pub fn mode(self, is_terminal: bool) -> ColorMode {
+        match self {
+            Self::Auto => ColorMode::auto(is_terminal),
+            Self::Truecolor => ColorMode::TrueColor,
+            Self::Xterm256 => ColorMode::Xterm256,
+            Self::Ansi => ColorMode::Ansi,
+            Self::None => ColorMode::None,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Format {
+    /// Colored charachters for the terminal
+    Ansi,
+    /// Colored half blocks for the terminal, doubling the vertical resolution
+    HalfBlock,
+    /// A SVG image
+    Svg,
+    /// A PNG image
+    Png,
+    /// A sixel image for terminals supporting the sixel graphics protocol
+    Sixel,
+    /// An image for terminals supporting the kitty graphics protocol
+    Kitty,
+    /// An inline image for iTerm2 and WezTerm
+    Iterm,
+}
+
+impl Format {
+    /// Chooses the best format the terminal supports
+    pub fn detect(is_terminal: bool) -> Self {
+        if is_terminal && crate::term::kitty() {
+            Self::Kitty
+        } else if is_terminal && crate::term::iterm() {
+            Self::Iterm
+        } else {
+            Self::Ansi
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Resolution {
+    pub width: NonZero<usize>,
+    pub height: NonZero<usize>,
+}
+
+impl PrintCli {
+    pub const fn transform(&self) -> Transform {
+        Transform {
+            rotation: self.rotate,
+            mirror: self.mirror,
+        }
+    }
+}
+
+#[derive(Parser, Clone)]
+pub struct SlideshowCli {
+    /// The names (or aliases) of the flags to show, defaults to all flags
+    #[arg(value_enum)]
+    pub flags: Vec<Flag>,
+    /// How many seconds each flag is shown
+    #[arg(short, long, value_parser = parse_duration, default_value = "3")]
+    pub seconds: Duration,
+    /// How the next flag appears
+    #[arg(short, long, value_enum, default_value_t = Transition::Fade)]
+    pub transition: Transition,
+    #[command(flatten)]
+    pub shape: ShapeArgs,
+    #[command(flatten)]
+    pub color: ColorArgs,
+}
+
+#[derive(Parser, Clone)]
+pub struct PaintCli {
+    /// The name (or alias) of the flag whose colors are used
+    #[arg(value_enum)]
+    pub flag: Flag,
+    /// How the stripes run through the text
+    #[arg(short, long, value_enum, default_value_t = Direction::Lines)]
+    pub direction: Direction,
+    /// How many lines or columns each stripe covers, 1 for lines and 2 for columns by default
+    #[arg(short, long)]
+    pub band: Option<NonZero<usize>>,
+    /// Only color the text, the background stays as it is
+    #[arg(short = 'F', long)]
+    pub foreground_only: bool,
+    #[command(flatten)]
+    pub color: ColorArgs,
+}
+
+#[derive(Parser, Clone)]
+pub struct BannerCli {
+    /// The name (or alias) of the flag to fill the text with
+    #[arg(value_enum)]
+    pub flag: Flag,
+    /// The text to print, multiple words are joined by spaces
+    #[arg(required = true)]
+    pub text: Vec<String>,
+    #[command(flatten)]
+    pub shape: ShapeArgs,
+    #[command(flatten)]
+    pub color: ColorArgs,
+}
+
+#[derive(Parser, Clone)]
+pub struct ThemeCli {
+    /// The name (or alias) of the flag to take the colors from
+    #[arg(value_enum)]
+    pub flag: Flag,
+    /// The terminal the color scheme is for
+    #[arg(short, long, value_enum)]
+    pub target: Target,
+    /// Write the color scheme to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser, Clone)]
+pub struct PaletteCli {
+    /// The name (or alias) of the flag to take the colors from
+    #[arg(value_enum)]
+    pub flag: Flag,
+    /// The format of the palette
+    #[arg(short, long, value_enum, default_value_t = palette::Format::Hex)]
+    pub format: palette::Format,
+    /// Write the palette to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
 #[derive(Parser)]
 pub struct Cli {
     #[command(subcommand)]
@@ -126,3 +369,47 @@ fn parse_width(s: &str) -> Result<Size, String> {
         }
     }
 }
+
+/// The largest raster image side in pixels, larger images would take up gigabytes of memory
+const MAX_RESOLUTION: usize = 16384;
+
+fn parse_resolution(s: &str) -> Result<Resolution, String> {
+    let Some((width, height)) = s.split_once('x') else {
+        return Err(String::from(
+            "The resolution must be given as <WIDTH>x<HEIGHT>",
+        ));
+    };
+
+    let parse = |s: &str| {
+        let pixels = s
+            .parse::<NonZero<usize>>()
+            .map_err(|err| match err.kind() {
+                IntErrorKind::Zero => String::from("The resolution must be greater than zero"),
+                IntErrorKind::Empty => String::from("The resolution must not be empty"),
+                IntErrorKind::PosOverflow => format!(
+                    "Your provided resolution doesn't fit into the integer (1-{})",
+                    usize::MAX
+                ),
+                _ => String::from("Please provide a number"),
+            })?;
+        if pixels.get() > MAX_RESOLUTION {
+            return Err(format!(
+                "The resolution must not exceed {MAX_RESOLUTION} pixels per side"
+            ));
+        }
+        Ok(pixels)
+    };
+
+    Ok(Resolution {
+        width: parse(width)?,
+        height: parse(height)?,
+    })
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let secs = s
+        .parse::<f64>()
+        .map_err(|_| String::from("Please provide a number of seconds"))?;
+    Duration::try_from_secs_f64(secs)
+        .map_err(|_| String::from("The duration must be a positive number of seconds"))
+}
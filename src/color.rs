@@ -3,6 +3,53 @@ pub const RESET: &str = "\x1b[0m";
 #[derive(Clone, Copy)]
 pub struct Color(u8, u8, u8);
 
+/// Converts a gamma encoded sRGB channel to linear light from 0 to 1
+fn linear(channel: u8) -> f32 {
+    let c = f32::from(channel) / 255.0;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// 24-bit colors
+    TrueColor,
+    /// The 256 colors of the xterm palette
+    Xterm256,
+    /// The 16 ANSI colors, the 8 basic ones and their bright versions
+    Ansi,
+    /// No escape codes at all
+    None,
+}
+
+impl ColorMode {
+    /// Chooses the color mode for an output, colors are disabled if `NO_COLOR` is set or the
+    /// output isn't a terminal, unless `CLICOLOR_FORCE` is set
+    pub fn auto(is_terminal: bool) -> Self {
+        if crate::term::no_color() {
+            Self::None
+        } else if is_terminal || crate::term::force_color() {
+            Self::detect()
+        } else {
+            Self::None
+        }
+    }
+
+    /// Detects the color mode supported by the terminal from the environment
+    pub fn detect() -> Self {
+        if crate::term::true_color() {
+            Self::TrueColor
+        } else if crate::term::xterm256() {
+            Self::Xterm256
+        } else {
+            Self::Ansi
+        }
+    }
+}
+
 pub trait AnsiCode {
     fn fg(&self) -> String;
     fn bg(&self) -> String;
@@ -19,6 +66,156 @@ impl Color {
     pub const fn gray(lightness: u8) -> Self {
         Self(lightness, lightness, lightness)
     }
+
+    pub const fn rgb(&self) -> [u8; 3] {
+        [self.0, self.1, self.2]
+    }
+
+    /// Blends linearly into `other`, `amount` goes from 0 (`self`) to 1 (`other`)
+    pub fn mix(self, other: Self, amount: f32) -> Self {
+        let channel = |a: u8, b: u8| {
+            (f32::from(a) + (f32::from(b) - f32::from(a)) * amount.clamp(0.0, 1.0)).round() as u8
+        };
+        Self(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+
+    /// The relative luminance as defined by WCAG, from 0 for black to 1 for white
+    pub fn luminance(&self) -> f32 {
+        0.2126 * linear(self.0) + 0.7152 * linear(self.1) + 0.0722 * linear(self.2)
+    }
+
+    /// The WCAG contrast ratio between two colors, from 1 to 21
+    pub fn contrast(&self, other: Self) -> f32 {
+        let (a, b) = (self.luminance() + 0.05, other.luminance() + 0.05);
+        a.max(b) / a.min(b)
+    }
+
+    /// The color in the CIELAB color space under the D65 white point
+    pub fn lab(&self) -> [f32; 3] {
+        let [r, g, b] = [self.0, self.1, self.2].map(linear);
+        let x = (0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b) / 0.950_47;
+        let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+        let z = (0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b) / 1.088_83;
+
+        let f = |t: f32| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    /// How different two colors look according to CIEDE2000, about 1 is just noticeable
+    pub fn distance(&self, other: Self) -> f32 {
+        let ([l1, a1, b1], [l2, a2, b2]) = (self.lab(), other.lab());
+
+        // Goes from 0 for gray to 1 for very colorful
+        let colorfulness = |chroma: f32| (chroma.powi(7) / (chroma.powi(7) + 25f32.powi(7))).sqrt();
+
+        let chroma_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+        let g = 0.5 * (1.0 - colorfulness(chroma_mean));
+        let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+        let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+        let hue = |a: f32, b: f32| {
+            if a == 0.0 && b == 0.0 {
+                0.0
+            } else {
+                b.atan2(a).to_degrees().rem_euclid(360.0)
+            }
+        };
+        let (h1, h2) = (hue(a1, b1), hue(a2, b2));
+
+        let delta_l = l2 - l1;
+        let delta_c = c2 - c1;
+        let delta_h = match h2 - h1 {
+            _ if c1 * c2 == 0.0 => 0.0,
+            d if d > 180.0 => d - 360.0,
+            d if d < -180.0 => d + 360.0,
+            d => d,
+        };
+        let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+        let l_mean = (l1 + l2) / 2.0;
+        let c_mean = (c1 + c2) / 2.0;
+        let h_mean = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let cos = |degrees: f32| degrees.to_radians().cos();
+        let t = 1.0 - 0.17 * cos(h_mean - 30.0)
+            + 0.24 * cos(2.0 * h_mean)
+            + 0.32 * cos(3.0 * h_mean + 6.0)
+            - 0.20 * cos(4.0 * h_mean - 63.0);
+        let rotation = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+        let r_c = 2.0 * colorfulness(c_mean);
+        let l_offset = (l_mean - 50.0).powi(2);
+        let s_l = 1.0 + 0.015 * l_offset / (20.0 + l_offset).sqrt();
+        let s_c = 1.0 + 0.045 * c_mean;
+        let s_h = 1.0 + 0.015 * c_mean * t;
+        let r_t = -(2.0 * rotation).to_radians().sin() * r_c;
+
+        let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+        (l * l + c * c + h * h + r_t * c * h).sqrt()
+    }
+
+    /// The color as a hex string like `#ff00aa`
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl Color {
+    /// Colors are written as true color, in [`ColorMode::Xterm256`] and [`ColorMode::Ansi`] the
+    /// closest palette color is used and in [`ColorMode::None`] nothing
+    pub fn fg_in(&self, mode: ColorMode) -> String {
+        match mode {
+            ColorMode::TrueColor => self.fg(),
+            ColorMode::Xterm256 => IndexedColor::from(*self).fg(),
+            ColorMode::Ansi => AnsiColor::nearest(*self).fg(),
+            ColorMode::None => String::new(),
+        }
+    }
+
+    /// See [`Color::fg_in`]
+    pub fn bg_in(&self, mode: ColorMode) -> String {
+        match mode {
+            ColorMode::TrueColor => self.bg(),
+            ColorMode::Xterm256 => IndexedColor::from(*self).bg(),
+            ColorMode::Ansi => AnsiColor::nearest(*self).bg(),
+            ColorMode::None => String::new(),
+        }
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    /// Parses a hex color like `#ff00aa`, the `#` is optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(format!("'{s}' is not a hex color like #ff00aa"));
+        }
+
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| format!("'{s}' is not a hex color like #ff00aa"))
+        };
+        Ok(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
 }
 
 impl AnsiCode for Color {
@@ -31,7 +228,7 @@ impl AnsiCode for Color {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum AnsiColor {
     Black = 30,
@@ -42,6 +239,80 @@ pub enum AnsiColor {
     Magenta = 35,
     Cyan = 36,
     White = 37,
+    BrightBlack = 90,
+    BrightRed = 91,
+    BrightGreen = 92,
+    BrightYellow = 93,
+    BrightBlue = 94,
+    BrightMagenta = 95,
+    BrightCyan = 96,
+    BrightWhite = 97,
+}
+
+impl AnsiColor {
+    /// The basic colors followed by the bright ones, in the order of the terminal palette
+    pub const ALL: [Self; 16] = [
+        Self::Black,
+        Self::Red,
+        Self::Green,
+        Self::Yellow,
+        Self::Blue,
+        Self::Magenta,
+        Self::Cyan,
+        Self::White,
+        Self::BrightBlack,
+        Self::BrightRed,
+        Self::BrightGreen,
+        Self::BrightYellow,
+        Self::BrightBlue,
+        Self::BrightMagenta,
+        Self::BrightCyan,
+        Self::BrightWhite,
+    ];
+
+    /// The color in the default xterm palette, terminals may be configured differently
+    pub const fn rgb(self) -> Color {
+        match self {
+            Self::Black => Color(0, 0, 0),
+            Self::Red => Color(205, 0, 0),
+            Self::Green => Color(0, 205, 0),
+            Self::Yellow => Color(205, 205, 0),
+            Self::Blue => Color(0, 0, 238),
+            Self::Magenta => Color(205, 0, 205),
+            Self::Cyan => Color(0, 205, 205),
+            Self::White => Color(229, 229, 229),
+            Self::BrightBlack => Color(127, 127, 127),
+            Self::BrightRed => Color(255, 0, 0),
+            Self::BrightGreen => Color(0, 255, 0),
+            Self::BrightYellow => Color(255, 255, 0),
+            Self::BrightBlue => Color(92, 92, 255),
+            Self::BrightMagenta => Color(255, 0, 255),
+            Self::BrightCyan => Color(0, 255, 255),
+            Self::BrightWhite => Color(255, 255, 255),
+        }
+    }
+
+    /// The position in the terminal palette, 0 to 7 for the basic colors and 8 to 15 for the
+    /// bright ones
+    pub const fn index(self) -> usize {
+        let code = self as usize;
+        if code >= Self::BrightBlack as usize {
+            code - Self::BrightBlack as usize + 8
+        } else {
+            code - Self::Black as usize
+        }
+    }
+
+    /// The ANSI color which looks the most like `color`
+    pub fn nearest(color: Color) -> Self {
+        Self::ALL
+            .into_iter()
+            .min_by(|a, b| {
+                let (a, b) = (color.distance(a.rgb()), color.distance(b.rgb()));
+                a.total_cmp(&b)
+            })
+            .unwrap_or(Self::Black)
+    }
 }
 
 impl AnsiCode for AnsiColor {
@@ -54,3 +325,128 @@ impl AnsiCode for AnsiColor {
         format!("\x1b[{}m", num + 10)
     }
 }
+
+impl std::str::FromStr for AnsiColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_lowercase().replace(['-', '_', ' '], "");
+        Ok(match name.as_str() {
+            "black" => Self::Black,
+            "red" => Self::Red,
+            "green" => Self::Green,
+            "yellow" => Self::Yellow,
+            "blue" => Self::Blue,
+            "magenta" => Self::Magenta,
+            "cyan" => Self::Cyan,
+            "white" => Self::White,
+            "brightblack" | "gray" | "grey" => Self::BrightBlack,
+            "brightred" => Self::BrightRed,
+            "brightgreen" => Self::BrightGreen,
+            "brightyellow" => Self::BrightYellow,
+            "brightblue" => Self::BrightBlue,
+            "brightmagenta" => Self::BrightMagenta,
+            "brightcyan" => Self::BrightCyan,
+            "brightwhite" => Self::BrightWhite,
+            _ => return Err(format!("'{s}' is not an ANSI color")),
+        })
+    }
+}
+
+/// A color of the xterm 256 color palette
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexedColor(pub u8);
+
+/// The channel values of the 6x6x6 color cube at the indices 16-231
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl IndexedColor {
+    /// The RGB value of the palette entry, the first 16 entries are configurable by the user and
+    /// have no fixed value, so they are never used
+    const fn rgb(index: u8) -> Option<Color> {
+        match index {
+            0..=15 => None,
+            16..=231 => {
+                let i = index - 16;
+                Some(Color(
+                    CUBE_LEVELS[(i / 36) as usize],
+                    CUBE_LEVELS[(i / 6 % 6) as usize],
+                    CUBE_LEVELS[(i % 6) as usize],
+                ))
+            }
+            232..=255 => Some(Color::gray(8 + (index - 232) * 10)),
+        }
+    }
+}
+
+impl From<Color> for IndexedColor {
+    /// Chooses the closest color of the cube or the gray ramp
+    fn from(color: Color) -> Self {
+        let distance = |other: Color| {
+            let [r, g, b] = color.rgb().map(i32::from);
+            let [or, og, ob] = other.rgb().map(i32::from);
+            // Weighted by how sensitive the eye is to each channel
+            2 * (r - or).pow(2) + 4 * (g - og).pow(2) + 3 * (b - ob).pow(2)
+        };
+
+        let index = (16..=255)
+            .filter_map(|index| Some((index, Self::rgb(index)?)))
+            .min_by_key(|(_, rgb)| distance(*rgb))
+            .map_or(16, |(index, _)| index);
+        Self(index)
+    }
+}
+
+impl AnsiCode for IndexedColor {
+    fn fg(&self) -> String {
+        format!("\x1b[38;5;{}m", self.0)
+    }
+
+    fn bg(&self) -> String {
+        format!("\x1b[48;5;{}m", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexed_color() {
+        assert_eq!(IndexedColor::from(Color::BLACK), IndexedColor(16));
+        assert_eq!(IndexedColor::from(Color::WHITE), IndexedColor(231));
+        assert_eq!(IndexedColor::from(Color::new(255, 0, 0)), IndexedColor(196));
+        assert_eq!(IndexedColor::from(Color::gray(127)), IndexedColor(244));
+        assert_eq!(
+            IndexedColor::from(Color::new(255, 174, 201)),
+            IndexedColor(218)
+        );
+    }
+
+    #[test]
+    fn test_nearest_ansi_color() {
+        assert_eq!(AnsiColor::nearest(Color::WHITE), AnsiColor::BrightWhite);
+        assert_eq!(AnsiColor::nearest(Color::gray(163)), AnsiColor::BrightBlack);
+        assert_eq!(AnsiColor::nearest(Color::gray(44)), AnsiColor::Black);
+        assert_eq!(
+            AnsiColor::nearest(Color::new(74, 129, 35)),
+            AnsiColor::Green
+        );
+        assert_eq!(
+            AnsiColor::nearest(Color::new(255, 140, 0)),
+            AnsiColor::BrightRed
+        );
+        assert_eq!(AnsiColor::index(AnsiColor::BrightBlack), 8);
+        assert_eq!("bright-cyan".parse(), Ok(AnsiColor::BrightCyan));
+    }
+
+    #[test]
+    fn test_color_modes() {
+        let color = Color::new(255, 255, 0);
+        assert_eq!(color.fg_in(ColorMode::TrueColor), "\x1b[38;2;255;255;0m");
+        assert_eq!(color.fg_in(ColorMode::Xterm256), "\x1b[38;5;226m");
+        assert_eq!(color.fg_in(ColorMode::Ansi), "\x1b[93m");
+        assert_eq!(color.bg_in(ColorMode::Ansi), "\x1b[103m");
+        assert_eq!(color.fg_in(ColorMode::None), "");
+    }
+}
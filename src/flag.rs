@@ -1,48 +1,107 @@
-use std::num::NonZero;
+use serde::Deserialize;
+use std::{num::NonZero, sync::OnceLock};
+
+pub mod custom;
 
 use crate::{
-    color::{AnsiCode, AnsiColor, Color},
+    color::{AnsiCode, AnsiColor, Color, ColorMode, IndexedColor},
     odd::Odd,
-    overlay::{Overlay, Size, charachter::OverlayCharachter, circle::Circle, triangle::Triangle},
+    overlay::{
+        Overlay, Size, charachter::OverlayCharachter, chevron::Chevron, circle::Circle,
+        triangle::Triangle,
+    },
 };
 
+#[derive(Clone, Copy)]
 pub struct FallbackedColor {
     true_color: Color,
-    ansi_color: AnsiColor,
+    /// A hand-picked fallback, the nearest ANSI color is used if there is none
+    ansi_color: Option<AnsiColor>,
 }
 
-impl AnsiCode for FallbackedColor {
-    fn fg(&self) -> String {
-        if crate::term::true_color() {
-            self.true_color.fg()
-        } else {
-            self.ansi_color.fg()
+impl FallbackedColor {
+    pub const fn new(ideal: Color, fallback: AnsiColor) -> Self {
+        Self {
+            true_color: ideal,
+            ansi_color: Some(fallback),
         }
     }
 
-    fn bg(&self) -> String {
-        if crate::term::true_color() {
-            self.true_color.bg()
-        } else {
-            self.ansi_color.bg()
+    /// A color whose fallback is the ANSI color which looks the most like it
+    pub const fn derived(ideal: Color) -> Self {
+        Self {
+            true_color: ideal,
+            ansi_color: None,
         }
     }
-}
 
-impl FallbackedColor {
-    const fn new(ideal: Color, fallback: AnsiColor) -> Self {
-        Self {
-            true_color: ideal,
-            ansi_color: fallback,
+    pub fn fg_in(&self, mode: ColorMode) -> String {
+        match mode {
+            ColorMode::TrueColor => self.true_color.fg(),
+            ColorMode::Xterm256 => IndexedColor::from(self.true_color).fg(),
+            ColorMode::Ansi => self.ansi_color().fg(),
+            ColorMode::None => String::new(),
         }
     }
+
+    pub const fn true_color(&self) -> Color {
+        self.true_color
+    }
+
+    pub fn ansi_color(&self) -> AnsiColor {
+        self.ansi_color
+            .unwrap_or_else(|| AnsiColor::nearest(self.true_color))
+    }
+
+    pub fn bg_in(&self, mode: ColorMode) -> String {
+        match mode {
+            ColorMode::TrueColor => self.true_color.bg(),
+            ColorMode::Xterm256 => IndexedColor::from(self.true_color).bg(),
+            ColorMode::Ansi => self.ansi_color().bg(),
+            ColorMode::None => String::new(),
+        }
+    }
+}
+
+/// How the stripes of a flag are arranged
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Layout {
+    /// Stripes from top to bottom
+    #[default]
+    Horizontal,
+    /// Stripes from left to right
+    Vertical,
+    /// Equally large fields from the top left to the bottom right, row by row. The stripes have to
+    /// fill every row, so their count is a multiple of `columns`
+    Grid { columns: NonZero<usize> },
+}
+
+/// A stripe of a built-in flag, the ANSI fallback is derived if it is left out
+macro_rules! stripe {
+    ($color:expr) => {
+        FallbackedColor::derived($color)
+    };
+    ($color:expr, $ansi:expr) => {
+        FallbackedColor::new($color, $ansi)
+    };
+}
+
+/// The weight of a stripe of a built-in flag, 1 if it is left out
+macro_rules! weight {
+    () => {
+        1
+    };
+    ($weight:expr) => {
+        $weight
+    };
 }
 
 macro_rules! flags {
     (
         $(
             $key:ident $(| $alt:expr)* => [
-                $( ($color:expr, $ansi:expr) ),+ $(,)?
+                $( ($color:expr $(, $ansi:expr)? $(; $weight:expr)?) ),+ $(,)?
             ]
         ),* $(,)?
     ) => {
@@ -51,11 +110,13 @@ macro_rules! flags {
         #[derive(Clone, Copy)]
         pub enum Flag {
             $($key,)*
+            /// A flag loaded at runtime, see [`custom`]
+            Custom(&'static custom::CustomFlag),
         }
 
         impl ValueEnum for Flag {
             fn value_variants<'a>() -> &'a [Self] {
-                &[$(Self::$key,)*]
+                VARIANTS.get_or_init(|| Self::BUILTIN.to_vec())
             }
 
             fn to_possible_value(&self) -> Option<PossibleValue> {
@@ -72,11 +133,23 @@ macro_rules! flags {
                                 ])
                         },
                     )*
+                    Self::Custom(flag) => {
+                        PossibleValue::new(flag.name.to_lowercase()).aliases(
+                            std::iter::once(flag.name.clone()).chain(
+                                flag.aliases
+                                    .iter()
+                                    .flat_map(|alt| [alt.clone(), alt.to_lowercase()]),
+                            ),
+                        )
+                    }
                 })
             }
         }
 
         impl Flag {
+            /// All flags compiled into jiman
+            pub const BUILTIN: &[Self] = &[$(Self::$key,)*];
+
             pub const VALUES: &[(&'static str, &[&'static str])] = &[
                 $(
                     (
@@ -86,144 +159,219 @@ macro_rules! flags {
                 )*
             ];
 
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$key => stringify!($key),)*
+                    Self::Custom(flag) => &flag.name,
+                }
+            }
+
+            pub fn aliases(&self) -> Vec<&'static str> {
+                match self {
+                    $(Self::$key => vec![$($alt,)*],)*
+                    Self::Custom(flag) => flag.aliases.iter().map(String::as_str).collect(),
+                }
+            }
+
             #[inline]
             pub fn stripes(&self) -> Vec<FallbackedColor> {
                 match self {
                     $(
                         Self::$key => vec![
-                            $( FallbackedColor::new($color, $ansi) ),*
+                            $( stripe!($color $(, $ansi)?) ),*
                         ],
                     )*
+                    Self::Custom(flag) => flag.stripes.clone(),
+                }
+            }
+
+            /// How many parts of the height (or width for vertical stripes) each of the
+            /// [`Flag::stripes`] takes up, grids ignore them
+            pub fn weights(&self) -> Vec<usize> {
+                match self {
+                    $(
+                        Self::$key => vec![$( weight!($($weight)?) ),*],
+                    )*
+                    Self::Custom(flag) => flag.weights.clone(),
                 }
             }
         }
     };
 }
 
+/// The built-in flags followed by the registered custom ones
+static VARIANTS: OnceLock<Vec<Flag>> = OnceLock::new();
+
 flags! {
     Lgbtqia | "Lgbtqia+" => [
         (Color::new(228, 3, 3), AnsiColor::Red),
-        (Color::new(255, 140, 0), AnsiColor::Red),
-        (Color::new(255, 237, 0), AnsiColor::Yellow),
+        (Color::new(255, 140, 0), AnsiColor::BrightRed),
+        (Color::new(255, 237, 0), AnsiColor::BrightYellow),
         (Color::new(0, 128, 38), AnsiColor::Green),
-        (Color::new(0, 77, 255), AnsiColor::Blue),
+        (Color::new(0, 77, 255), AnsiColor::BrightBlue),
+        (Color::new(117, 7, 135), AnsiColor::Magenta)
+    ],
+    Progress | "ProgressPride" => [
+        (Color::new(228, 3, 3), AnsiColor::Red),
+        (Color::new(255, 140, 0), AnsiColor::BrightRed),
+        (Color::new(255, 237, 0), AnsiColor::BrightYellow),
+        (Color::new(0, 128, 38), AnsiColor::Green),
+        (Color::new(0, 77, 255), AnsiColor::BrightBlue),
         (Color::new(117, 7, 135), AnsiColor::Magenta)
     ],
     Lgbt => [
         (Color::new(228, 3, 3), AnsiColor::Red),
-        (Color::new(255, 140, 0), AnsiColor::Red),
-        (Color::new(255, 237, 0), AnsiColor::Yellow),
+        (Color::new(255, 140, 0), AnsiColor::BrightRed),
+        (Color::new(255, 237, 0), AnsiColor::BrightYellow),
         (Color::new(0, 128, 38), AnsiColor::Green),
-        (Color::new(0, 77, 255), AnsiColor::Blue),
+        (Color::new(0, 77, 255), AnsiColor::BrightBlue),
         (Color::new(117, 7, 135), AnsiColor::Magenta)
     ],
     Asexual | "Ace" => [
         (Color::BLACK, AnsiColor::Black),
-        (Color::gray(163), AnsiColor::Black),
-        (Color::WHITE, AnsiColor::White),
+        (Color::gray(163)),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(128, 0, 128), AnsiColor::Magenta),
     ],
     Aromantic | "Aro" => [
         (Color::new(62, 167,68), AnsiColor::Green),
         (Color::new(169, 212, 120), AnsiColor::Green),
-        (Color::WHITE, AnsiColor::White),
-        (Color::gray(170), AnsiColor::Black),
+        (Color::WHITE, AnsiColor::BrightWhite),
+        (Color::gray(170)),
         (Color::BLACK, AnsiColor::Black),
     ],
     Aroace => [
         (Color::new(227, 140, 1), AnsiColor::Red),
         (Color::new(236, 205, 0), AnsiColor::Yellow),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(98, 175, 222), AnsiColor::Cyan),
         (Color::new(32, 56, 87), AnsiColor::Blue),
     ],
     Bisexual | "Bi" => [
-        (Color::new(214, 2, 122), AnsiColor::Magenta),
-        (Color::new(214, 2, 122), AnsiColor::Magenta),
+        (Color::new(214, 2, 122), AnsiColor::BrightMagenta; 2),
         (Color::new(155, 79, 150), AnsiColor::Magenta),
-        (Color::new(0, 56, 168), AnsiColor::Blue),
-        (Color::new(0, 56, 168), AnsiColor::Blue),
+        (Color::new(0, 56, 168), AnsiColor::Blue; 2),
     ],
     Polysexual | "Poly" => [
-        (Color::new(246, 28, 185), AnsiColor::Magenta),
+        (Color::new(246, 28, 185), AnsiColor::BrightMagenta),
         (Color::new(7, 218, 105), AnsiColor::Green),
-        (Color::new(28, 146, 246), AnsiColor::Cyan),
+        (Color::new(28, 146, 246), AnsiColor::BrightBlue),
     ],
     Pansexual | "Pan" => [
-        (Color::new(255, 33, 140), AnsiColor::Magenta),
+        (Color::new(255, 33, 140), AnsiColor::BrightMagenta),
         (Color::new(255, 216, 0), AnsiColor::Yellow),
         (Color::new(33, 177, 255), AnsiColor::Cyan),
     ],
     Lesbian => [
         (Color::new(214, 44,0), AnsiColor::Red),
-        (Color::new(255, 153, 86), AnsiColor::Red),
-        (Color::WHITE, AnsiColor::White),
+        (Color::new(255, 153, 86), AnsiColor::BrightRed),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(211, 98,164), AnsiColor::Magenta),
         (Color::new(164, 1, 98), AnsiColor::Magenta),
     ],
     Gay => [
         (Color::new(7, 141, 112), AnsiColor::Green),
         (Color::new(152, 232, 193), AnsiColor::Green),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(123, 173, 226), AnsiColor::Cyan),
         (Color::new(61, 26, 120), AnsiColor::Blue),
     ],
     Demisexual => [
-        (Color::WHITE, AnsiColor::White),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(128, 0, 128), AnsiColor::Magenta),
-        (Color::gray(210), AnsiColor::Black),
-        (Color::gray(210), AnsiColor::Black),
+        (Color::gray(210)),
+        (Color::gray(210)),
     ],
     Demiromantic => [
-        (Color::WHITE, AnsiColor::White),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(51,  138, 55), AnsiColor::Green),
-        (Color::gray(210), AnsiColor::Black),
-        (Color::gray(210), AnsiColor::Black),
+        (Color::gray(210)),
+        (Color::gray(210)),
     ],
     Trans => [
         (Color::new(115, 207, 244), AnsiColor::Cyan),
         (Color::new(238, 175, 192), AnsiColor::Magenta),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(238, 175, 192), AnsiColor::Magenta),
         (Color::new(115, 207, 244), AnsiColor::Cyan),
     ],
     Nonbinary => [
-        (Color::new(252, 244, 52), AnsiColor::Yellow),
-        (Color::WHITE, AnsiColor::White),
+        (Color::new(252, 244, 52), AnsiColor::BrightYellow),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new( 156, 89, 209), AnsiColor::Magenta),
         (Color::gray(44), AnsiColor::Black),
     ],
     Polyamory => [
         (Color::new(0, 0, 255), AnsiColor::Blue),
-        (Color::new(255, 0, 0), AnsiColor::Red),
+        (Color::new(255, 0, 0), AnsiColor::BrightRed),
         (Color::BLACK, AnsiColor::Black),
     ],
     Demigirl => [
-        (Color::gray(127), AnsiColor::Black),
+        (Color::gray(127), AnsiColor::BrightBlack),
         (Color::gray(196), AnsiColor::White),
         (Color::new(255,174,201), AnsiColor::Magenta),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(255,174,201), AnsiColor::Magenta),
         (Color::gray(196), AnsiColor::White),
-        (Color::gray(127), AnsiColor::Black),
+        (Color::gray(127), AnsiColor::BrightBlack),
     ],
     Demiboy => [
-        (Color::gray(127), AnsiColor::Black),
+        (Color::gray(127), AnsiColor::BrightBlack),
         (Color::gray(196), AnsiColor::White),
         (Color::new(193,217,235), AnsiColor::Cyan),
-        (Color::WHITE, AnsiColor::White),
+        (Color::WHITE, AnsiColor::BrightWhite),
         (Color::new(193,217,235), AnsiColor::Cyan),
         (Color::gray(196), AnsiColor::White),
-        (Color::gray(127), AnsiColor::Black),
+        (Color::gray(127), AnsiColor::BrightBlack),
     ],
     Intersex | "Inter" => [
         (Color::new(252, 219, 4), AnsiColor::Yellow),
     ]
 }
 
+/// The colors of the chevrons on the Progress Pride flag, from the inside out
+const PROGRESS_CHEVRONS: [Color; 5] = [
+    Color::WHITE,
+    Color::new(244, 174, 200),
+    Color::new(123, 204, 229),
+    Color::new(148, 85, 22),
+    Color::BLACK,
+];
+
+/// The width of each chevron on the Progress Pride flag in charachters
+const CHEVRON_WIDTH: usize = 3;
+
+/// Nested chevrons starting at `insert`, from the inside out
+fn chevrons(
+    colors: &'static [Color],
+    insert: usize,
+    slope: NonZero<usize>,
+) -> impl Iterator<Item = Box<dyn Overlay<Foreground = Color>>> {
+    colors
+        .iter()
+        .enumerate()
+        .map(move |(i, &color)| -> Box<dyn Overlay<Foreground = Color>> {
+            Box::new(Chevron::new(
+                0,
+                insert + CHEVRON_WIDTH * i,
+                CHEVRON_WIDTH,
+                slope,
+                color,
+            ))
+        })
+}
+
 impl Flag {
+    /// All built-in flags have horizontal stripes, other layouts come from custom flags
+    pub const fn layout(&self) -> Layout {
+        match self {
+            Self::Custom(flag) => flag.layout,
+            _ => Layout::Horizontal,
+        }
+    }
+
     pub fn overlays(
         &self,
         slope: NonZero<usize>,
@@ -237,16 +385,8 @@ impl Flag {
 
                 let insert = (diameter.value() as f32 * 1.5) as usize;
 
-                let colors = [
-                    Color::WHITE,
-                    Color::new(244, 174, 200),
-                    Color::new(123, 204, 229),
-                    Color::new(148, 85, 22),
-                    Color::BLACK,
-                ];
-
                 let mut res: Vec<Box<dyn Overlay<Foreground = Color>>> =
-                    Vec::with_capacity(colors.len() + 2);
+                    Vec::with_capacity(PROGRESS_CHEVRONS.len() + 2);
 
                 res.push(Box::new(Circle::new(
                     (
@@ -265,14 +405,21 @@ impl Flag {
                     Color::new(253, 216, 23),
                 )));
 
-                for (i, &color) in colors.iter().enumerate() {
-                    res.push(Box::new(Triangle::new(
-                        0,
-                        insert + 3 * (i + 1),
-                        slope,
-                        color,
-                    )));
-                }
+                res.extend(chevrons(&PROGRESS_CHEVRONS, insert, slope));
+
+                res
+            }
+            Self::Progress => {
+                let mut res: Vec<Box<dyn Overlay<Foreground = Color>>> =
+                    Vec::with_capacity(PROGRESS_CHEVRONS.len());
+
+                res.push(Box::new(Triangle::new(
+                    0,
+                    CHEVRON_WIDTH,
+                    slope,
+                    PROGRESS_CHEVRONS[0],
+                )));
+                res.extend(chevrons(&PROGRESS_CHEVRONS[1..], CHEVRON_WIDTH, slope));
 
                 res
             }
@@ -294,7 +441,59 @@ impl Flag {
                     Color::new(137, 42, 136),
                 ))]
             }
+            Self::Custom(flag) => flag.overlays(slope, size),
             _ => Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stripes whose hand-picked fallback differs from the nearest ANSI color on purpose, mostly
+    /// to keep neighbouring stripes apart
+    const OVERRIDES: &[(&str, &str)] = &[
+        ("Lgbtqia", "#750787"),
+        ("Progress", "#750787"),
+        ("Lgbt", "#750787"),
+        ("Aroace", "#e38c01"),
+        ("Bisexual", "#d6027a"),
+        ("Gay", "#078d70"),
+        ("Gay", "#98e8c1"),
+        ("Gay", "#7bade2"),
+        ("Trans", "#eeafc0"),
+        ("Demigirl", "#ffaec9"),
+        ("Demiboy", "#c1d9eb"),
+    ];
+
+    #[test]
+    fn test_fallbacks() {
+        let mut overridden = Vec::new();
+        for flag in Flag::BUILTIN {
+            for stripe in flag.stripes() {
+                let Some(hint) = stripe.ansi_color else {
+                    continue;
+                };
+                let nearest = AnsiColor::nearest(stripe.true_color);
+                let key = (flag.name(), stripe.true_color.hex());
+                if hint != nearest {
+                    overridden.push(key.clone());
+                }
+                assert!(
+                    hint == nearest || OVERRIDES.contains(&(key.0, key.1.as_str())),
+                    "{} {} falls back to {hint:?} but looks the most like {nearest:?}",
+                    key.0,
+                    key.1,
+                );
+            }
+        }
+
+        for (flag, hex) in OVERRIDES {
+            assert!(
+                overridden.contains(&(flag, hex.to_string())),
+                "{flag} {hex} is listed as an override but falls back to the nearest color"
+            );
+        }
+    }
+}
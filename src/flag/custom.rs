@@ -0,0 +1,615 @@
+//! Flags defined in TOML or JSON files which are loaded at runtime.
+//!
+//! ```toml
+//! name = "Genderqueer"
+//! aliases = ["Gq"]
+//!
+//! [[stripes]]
+//! color = "#b57edc"
+//! ansi = "magenta"
+//!
+//! [[stripes]]
+//! color = "#ffffff"
+//! ansi = "white"
+//!
+//! [[stripes]]
+//! color = "#4a8123"
+//! ansi = "green"
+//!
+//! [[overlays]]
+//! type = "triangle"
+//! color = "#000000"
+//! ```
+
+use super::{FallbackedColor, Flag, Layout, VARIANTS};
+use crate::{
+    color::{AnsiColor, Color},
+    odd::Odd,
+    overlay::{
+        Overlay, Size,
+        anchor::{Anchor, Offset, Placement, Side},
+        charachter::OverlayCharachter,
+        chevron::Chevron,
+        circle::Circle,
+        star::Star,
+        triangle::Triangle,
+    },
+};
+use serde::{Deserialize, Deserializer, de::Error as _};
+use std::{
+    env, fmt, fs, io,
+    num::NonZero,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+pub struct CustomFlag {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub stripes: Vec<FallbackedColor>,
+    pub layout: Layout,
+    /// How many parts of the height (or width) each stripe takes up
+    pub weights: Vec<usize>,
+    overlays: Vec<OverlayDefinition>,
+}
+
+#[derive(Deserialize)]
+struct Definition {
+    name: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    stripes: Vec<StripeDefinition>,
+    #[serde(default)]
+    layout: Layout,
+    #[serde(default)]
+    overlays: Vec<OverlayDefinition>,
+}
+
+#[derive(Deserialize)]
+struct StripeDefinition {
+    #[serde(deserialize_with = "from_str")]
+    color: Color,
+    /// Derived from the color if it's left out
+    #[serde(default, deserialize_with = "from_optional_str")]
+    ansi: Option<AnsiColor>,
+    #[serde(default = "default_weight")]
+    weight: usize,
+}
+
+const fn default_weight() -> usize {
+    1
+}
+
+/// How many parts a stripe can take up, finer splits wouldn't show on any terminal
+const WEIGHTS: RangeInclusive<usize> = 1..=1000;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum OverlayDefinition {
+    /// A triangle on the hoist or the fly whose slope is set with `--slope`
+    Triangle {
+        #[serde(deserialize_with = "from_str")]
+        color: Color,
+        /// How many charachters the triangle is moved away from its edge
+        #[serde(default)]
+        insert: usize,
+        /// How many rows are left empty above and below the triangle
+        #[serde(default)]
+        padding: usize,
+        #[serde(default, rename = "anchor", deserialize_with = "from_anchor")]
+        side: Side,
+    },
+    /// A band pointing away from its edge, leaving out the first `insert` columns
+    Chevron {
+        #[serde(deserialize_with = "from_str")]
+        color: Color,
+        #[serde(default)]
+        insert: usize,
+        /// How many charachters wide the band is
+        #[serde(default = "default_chevron_width")]
+        width: usize,
+        #[serde(default)]
+        padding: usize,
+        #[serde(default, rename = "anchor", deserialize_with = "from_anchor")]
+        side: Side,
+    },
+    /// A ring, in the center of the flag by default
+    Circle {
+        #[serde(deserialize_with = "from_str")]
+        color: Color,
+        /// The diameter relative to the height of the flag
+        #[serde(default = "default_diameter")]
+        diameter: f32,
+        #[serde(flatten)]
+        placement: Placement,
+    },
+    /// A star, in the center of the flag by default
+    Star {
+        #[serde(deserialize_with = "from_str")]
+        color: Color,
+        #[serde(default = "default_star_points")]
+        points: NonZero<usize>,
+        /// The radius of the tips relative to the height of the flag
+        #[serde(default = "default_star_outer")]
+        outer: f32,
+        /// The radius of the notches between the tips relative to the height of the flag
+        #[serde(default = "default_star_inner")]
+        inner: f32,
+        #[serde(flatten)]
+        placement: Placement,
+    },
+    /// A single charachter, in the center of the flag by default
+    #[serde(alias = "charachter")]
+    Character {
+        char: char,
+        #[serde(deserialize_with = "from_str")]
+        color: Color,
+        #[serde(flatten)]
+        placement: Placement,
+    },
+}
+
+const fn default_chevron_width() -> usize {
+    3
+}
+
+const fn default_diameter() -> f32 {
+    0.5
+}
+
+/// How many points a star can have, more would only blur into a circle
+const STAR_POINTS: RangeInclusive<usize> = 3..=32;
+
+/// How many cells an overlay can be moved, padded or widened, anything further is off the flag
+const MAX_OFFSET: usize = 10_000;
+
+impl OverlayDefinition {
+    /// Checks the numbers whose range isn't already limited by their type
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Triangle {
+                insert, padding, ..
+            } => validate_cells(&[*insert, *padding]),
+            Self::Chevron {
+                insert,
+                width,
+                padding,
+                ..
+            } => {
+                if *width == 0 {
+                    return Err(String::from("A chevron must be at least 1 charachter wide"));
+                }
+                validate_cells(&[*insert, *width, *padding])
+            }
+            Self::Circle {
+                diameter,
+                placement,
+                ..
+            } => {
+                if !(diameter.is_finite() && *diameter > 0.0 && *diameter <= 1.0) {
+                    return Err(format!(
+                        "The diameter of a circle must be above 0 and at most 1, not {diameter}"
+                    ));
+                }
+                validate_placement(placement)
+            }
+            Self::Star {
+                points,
+                outer,
+                inner,
+                placement,
+                ..
+            } => {
+                if !STAR_POINTS.contains(&points.get()) {
+                    return Err(format!(
+                        "A star must have {} to {} points, not {points}",
+                        STAR_POINTS.start(),
+                        STAR_POINTS.end()
+                    ));
+                }
+                let valid = outer.is_finite()
+                    && inner.is_finite()
+                    && 0.0 < *inner
+                    && inner < outer
+                    && *outer <= 1.0;
+                if !valid {
+                    return Err(format!(
+                        "The radii of a star must satisfy 0 < inner < outer <= 1, not {inner} and {outer}"
+                    ));
+                }
+                validate_placement(placement)
+            }
+            Self::Character { placement, .. } => validate_placement(placement),
+        }
+    }
+}
+
+/// Checks the insert, width and padding of a triangle or chevron
+fn validate_cells(cells: &[usize]) -> Result<(), String> {
+    if cells.iter().all(|&cells| cells <= MAX_OFFSET) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Triangles and chevrons can be inserted, padded and widened by at most {MAX_OFFSET} cells"
+        ))
+    }
+}
+
+fn validate_placement(placement: &Placement) -> Result<(), String> {
+    let valid = [placement.x, placement.y]
+        .into_iter()
+        .all(|offset| match offset {
+            Offset::Cells(cells) => cells.unsigned_abs() <= MAX_OFFSET,
+            Offset::Percent(percent) => percent.is_finite() && percent.abs() <= 100.0,
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Overlays can be moved by at most {MAX_OFFSET} cells or 100% away from their anchor"
+        ))
+    }
+}
+
+const fn default_star_points() -> NonZero<usize> {
+    NonZero::new(5).unwrap()
+}
+
+const fn default_star_outer() -> f32 {
+    0.3
+}
+
+const fn default_star_inner() -> f32 {
+    0.12
+}
+
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(D::Error::custom)
+}
+
+fn from_optional_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let s = Option::<String>::deserialize(deserializer)?;
+    s.map(|s| s.parse().map_err(D::Error::custom)).transpose()
+}
+
+fn from_anchor<'de, D>(deserializer: D) -> Result<Side, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Side::try_from(Anchor::deserialize(deserializer)?).map_err(D::Error::custom)
+}
+
+impl CustomFlag {
+    pub fn from_toml(s: &str) -> Result<Self, LoadError> {
+        let definition = toml::from_str::<Definition>(s).map_err(LoadError::Toml)?;
+        definition.try_into().map_err(LoadError::Invalid)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, LoadError> {
+        let definition = serde_json::from_str::<Definition>(s).map_err(LoadError::Json)?;
+        definition.try_into().map_err(LoadError::Invalid)
+    }
+
+    /// Loads a definition, the format is chosen by the file extension
+    pub fn from_file(path: &Path) -> Result<Self, LoadError> {
+        let content = fs::read_to_string(path).map_err(LoadError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&content),
+            _ => Self::from_toml(&content),
+        }
+    }
+
+    pub(super) fn overlays(
+        &self,
+        slope: NonZero<usize>,
+        size: Size,
+    ) -> Vec<Box<dyn Overlay<Foreground = Color>>> {
+        self.overlays
+            .iter()
+            .map(|overlay| -> Box<dyn Overlay<Foreground = Color>> {
+                match *overlay {
+                    OverlayDefinition::Triangle {
+                        color,
+                        insert,
+                        padding,
+                        side,
+                    } => Box::new(Triangle::new(padding, insert, slope, color).with_side(side)),
+                    OverlayDefinition::Chevron {
+                        color,
+                        insert,
+                        width,
+                        padding,
+                        side,
+                    } => {
+                        Box::new(Chevron::new(padding, insert, width, slope, color).with_side(side))
+                    }
+                    OverlayDefinition::Circle {
+                        color,
+                        diameter,
+                        placement,
+                    } => {
+                        let basis = (size.height as f32 * diameter) as usize;
+                        let diameter = Odd::<usize>::new(basis)
+                            .unwrap_or_else(|| Odd::<usize>::new_panics(basis + 1));
+                        let center = placement.center(size, Circle::extent(diameter));
+                        Box::new(Circle::new(center, diameter, color))
+                    }
+                    OverlayDefinition::Star {
+                        color,
+                        points,
+                        outer,
+                        inner,
+                        placement,
+                    } => {
+                        let (outer, inner) =
+                            (size.height as f32 * outer, size.height as f32 * inner);
+                        let center = placement.center(size, Star::extent(outer));
+                        Box::new(Star::new(center, points, outer, inner, color))
+                    }
+                    OverlayDefinition::Character {
+                        char,
+                        color,
+                        placement,
+                    } => {
+                        let coord = placement.center(size, Size::new(1, 1));
+                        Box::new(OverlayCharachter::new(char, color, coord))
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<Definition> for CustomFlag {
+    type Error = String;
+
+    fn try_from(definition: Definition) -> Result<Self, Self::Error> {
+        if definition.name.is_empty() {
+            return Err(String::from("The name must not be empty"));
+        }
+        if definition.stripes.is_empty() {
+            return Err(String::from("A flag needs at least one stripe"));
+        }
+        for stripe in &definition.stripes {
+            if !WEIGHTS.contains(&stripe.weight) {
+                return Err(format!(
+                    "The weight of a stripe must be {} to {}, not {}",
+                    WEIGHTS.start(),
+                    WEIGHTS.end(),
+                    stripe.weight
+                ));
+            }
+        }
+        if let Layout::Grid { columns } = definition.layout
+            && definition.stripes.len() % columns != 0
+        {
+            return Err(format!(
+                "A grid with {columns} columns needs a multiple of {columns} stripes, not {}",
+                definition.stripes.len()
+            ));
+        }
+        for overlay in &definition.overlays {
+            overlay.validate()?;
+        }
+
+        Ok(Self {
+            name: definition.name,
+            aliases: definition.aliases,
+            layout: definition.layout,
+            weights: definition
+                .stripes
+                .iter()
+                .map(|stripe| stripe.weight)
+                .collect(),
+            stripes: definition
+                .stripes
+                .into_iter()
+                .map(|stripe| match stripe.ansi {
+                    Some(ansi) => FallbackedColor::new(stripe.color, ansi),
+                    None => FallbackedColor::derived(stripe.color),
+                })
+                .collect(),
+            overlays: definition.overlays,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Toml(err) => write!(f, "{err}"),
+            Self::Json(err) => write!(f, "{err}"),
+            Self::Invalid(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// The directory custom flags are loaded from, `$XDG_CONFIG_HOME/jiman/flags` or
+/// `~/.config/jiman/flags`
+pub fn flags_dir() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?;
+    Some(config.join("jiman").join("flags"))
+}
+
+/// Loads all `.toml` and `.json` files in `dir`, a missing directory contains no flags
+pub fn load_dir(dir: &Path) -> Vec<(PathBuf, Result<CustomFlag, LoadError>)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Vec::new(),
+        Err(err) => return vec![(dir.to_path_buf(), Err(LoadError::Io(err)))],
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "toml" || ext == "json")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let flag = CustomFlag::from_file(&path);
+            (path, flag)
+        })
+        .collect()
+}
+
+/// Makes custom flags available in [`clap::ValueEnum::value_variants`], this has to happen before
+/// the command line is parsed and only the first call has an effect.
+///
+/// Flags whose name or aliases clash with an already known flag are not registered and returned.
+pub fn register(flags: Vec<CustomFlag>) -> Vec<CustomFlag> {
+    let mut variants = Flag::BUILTIN.to_vec();
+    let mut rejected = Vec::new();
+
+    for flag in flags {
+        let names = std::iter::once(&flag.name)
+            .chain(&flag.aliases)
+            .map(|name| name.to_lowercase())
+            .collect::<Vec<_>>();
+        let taken = variants.iter().any(|known| {
+            std::iter::once(known.name())
+                .chain(known.aliases())
+                .any(|name| names.contains(&name.to_lowercase()))
+        });
+
+        if taken {
+            rejected.push(flag);
+        } else {
+            variants.push(Flag::Custom(Box::leak(Box::new(flag))));
+        }
+    }
+
+    let _ = VARIANTS.set(variants);
+    rejected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_definition() {
+        let flag = CustomFlag::from_toml(
+            r##"
+            name = "Genderqueer"
+            aliases = ["Gq"]
+            stripes = [
+                { color = "#b57edc", ansi = "magenta" },
+                { color = "#ffffff", ansi = "white" },
+                { color = "#4a8123", weight = 2 },
+            ]
+            overlays = [
+                { type = "triangle", color = "#000000", insert = 2, anchor = "fly" },
+                { type = "character", char = "*", color = "#ffffff", anchor = "hoist", x = "10%", y = -1 },
+                { type = "star", color = "#ffffff", points = 6 },
+            ]
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(flag.name, "Genderqueer");
+        assert_eq!(flag.aliases, ["Gq"]);
+        assert_eq!(flag.stripes.len(), 3);
+        assert_eq!(flag.stripes[0].true_color().hex(), "#b57edc");
+        assert_eq!(flag.stripes[1].ansi_color(), AnsiColor::White);
+        assert_eq!(flag.stripes[2].ansi_color, None);
+        assert_eq!(flag.stripes[2].ansi_color(), AnsiColor::Green);
+        assert_eq!(flag.weights, [1, 1, 2]);
+        let size = Size::new(15, 71);
+        let overlays = flag.overlays(NonZero::<usize>::MIN, size);
+        assert_eq!(overlays.len(), 3);
+        assert_eq!(overlays[0].at_pos(70, 0, size), Some('█'));
+        assert_eq!(overlays[0].at_pos(0, 0, size), None);
+        assert_eq!(overlays[1].at_pos(7, 6, size), Some('*'));
+        assert_eq!(overlays[2].at_pos(35, 7, size), Some('█'));
+
+        let chevron = r##"
+            name = "Chevron"
+            stripes = [{ color = "#000000" }]
+            overlays = [{ type = "chevron", color = "#ffffff", anchor = "top" }]
+            "##;
+        assert!(CustomFlag::from_toml(chevron).is_err());
+
+        let overlay = |overlay: &str| {
+            CustomFlag::from_toml(&format!(
+                r##"
+                name = "Overlay"
+                stripes = [{{ color = "#000000" }}]
+                overlays = [{overlay}]
+                "##
+            ))
+        };
+        assert!(overlay(r##"{ type = "chevron", color = "#ffffff", width = 1 }"##).is_ok());
+        assert!(overlay(r##"{ type = "chevron", color = "#ffffff", width = 0 }"##).is_err());
+        assert!(overlay(r##"{ type = "chevron", color = "#ffffff", padding = 100000 }"##).is_err());
+        assert!(overlay(r##"{ type = "triangle", color = "#ffffff", insert = 100000 }"##).is_err());
+        assert!(overlay(r##"{ type = "circle", color = "#ffffff", diameter = 1.0 }"##).is_ok());
+        assert!(overlay(r##"{ type = "circle", color = "#ffffff", diameter = 1e30 }"##).is_err());
+        assert!(overlay(r##"{ type = "circle", color = "#ffffff", diameter = -0.5 }"##).is_err());
+        assert!(overlay(r##"{ type = "circle", color = "#ffffff", diameter = nan }"##).is_err());
+        assert!(
+            overlay(r##"{ type = "character", char = "*", color = "#ffffff", x = "150%" }"##)
+                .is_err()
+        );
+        assert!(
+            overlay(r##"{ type = "character", char = "*", color = "#ffffff", y = 1000000 }"##)
+                .is_err()
+        );
+
+        let weight = |weight: usize| {
+            CustomFlag::from_toml(&format!(
+                r##"
+                name = "Weight"
+                stripes = [{{ color = "#000000", weight = {weight} }}]
+                "##
+            ))
+        };
+        assert!(weight(1000).is_ok());
+        assert!(weight(1001).is_err());
+        assert!(weight(0).is_err());
+
+        let grid = |columns: usize| {
+            CustomFlag::from_toml(&format!(
+                r##"
+                name = "Grid"
+                layout = {{ type = "grid", columns = {columns} }}
+                stripes = [{{ color = "#000000" }}, {{ color = "#ffffff" }}]
+                "##
+            ))
+        };
+        assert!(grid(2).is_ok());
+        assert!(grid(3).is_err());
+        assert!(grid(100_000_000_000).is_err());
+
+        assert!(CustomFlag::from_toml(r#"name = "Empty""#).is_err());
+        assert!(CustomFlag::from_json(r#"{ "name": "Empty", "stripes": [] }"#).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! An encoder for the inline image protocol of iTerm2, which is also supported by WezTerm, see
+//! <https://iterm2.com/documentation-images.html>
+
+use crate::{base64, overlay::Size, png, render::raster::Image};
+use std::io;
+
+/// Writes `image` as a PNG, stretched over `size` charachter cells
+pub fn encode(out: &mut impl io::Write, image: &Image, size: Size) -> io::Result<()> {
+    let mut data = Vec::new();
+    png::encode(&mut data, image)?;
+
+    writeln!(
+        out,
+        "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0:{}\x07",
+        data.len(),
+        size.width,
+        size.height,
+        base64::encode(&data),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![[255, 0, 0], [0, 0, 255]],
+        };
+        let mut png = Vec::new();
+        png::encode(&mut png, &image).unwrap();
+
+        let mut buf = Vec::new();
+        encode(&mut buf, &image, Size::new(3, 7)).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+
+        let header = format!(
+            "\x1b]1337;File=inline=1;size={};width=7;height=3;preserveAspectRatio=0:",
+            png.len()
+        );
+        let data = out.strip_prefix(&header).unwrap();
+        let data = data.strip_suffix("\x07\n").unwrap();
+        assert_eq!(data, base64::encode(&png));
+    }
+}
@@ -0,0 +1,98 @@
+//! An encoder for the kitty graphics protocol, see
+//! <https://sw.kovidgoyal.net/kitty/graphics-protocol/>
+
+use crate::{base64, overlay::Size, png, render::raster::Image};
+use std::io;
+
+/// The maximum size of the base64 payload of a single escape sequence
+const CHUNK_SIZE: usize = 4096;
+
+/// Writes `image` as zlib compressed RGBA data, scaled to fit into `size` charachter cells
+pub fn encode(out: &mut impl io::Write, image: &Image, size: Size) -> io::Result<()> {
+    let rgba = image
+        .pixels
+        .iter()
+        .flat_map(|&[r, g, b]| [r, g, b, u8::MAX])
+        .collect::<Vec<_>>();
+    let payload = base64::encode(&png::zlib(&rgba));
+
+    // Every chunk is a multiple of 4 bytes long, so it can be split at any chunk boundary
+    let mut chunks = payload.as_bytes().chunks(CHUNK_SIZE).peekable();
+    let mut first = true;
+    while let Some(chunk) = chunks.next() {
+        let more = u8::from(chunks.peek().is_some());
+        let chunk = String::from_utf8_lossy(chunk);
+        if first {
+            write!(
+                out,
+                "\x1b_Ga=T,f=32,o=z,q=2,s={},v={},c={},r={},m={more};{chunk}\x1b\\",
+                image.width, image.height, size.width, size.height,
+            )?;
+            first = false;
+        } else {
+            write!(out, "\x1b_Gm={more};{chunk}\x1b\\")?;
+        }
+    }
+
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks() {
+        // Noise doesn't compress, so the payload needs several chunks
+        let mut seed = 1u32;
+        let pixels = (0..4000)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                seed.to_be_bytes()[..3].try_into().unwrap()
+            })
+            .collect();
+        let image = Image {
+            width: 80,
+            height: 50,
+            pixels,
+        };
+
+        let mut buf = Vec::new();
+        encode(&mut buf, &image, Size::new(5, 16)).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        let sequences = out
+            .strip_suffix('\n')
+            .unwrap()
+            .split_terminator("\x1b\\")
+            .collect::<Vec<_>>();
+        assert!(sequences.len() > 2);
+
+        let mut payload = String::new();
+        for (i, sequence) in sequences.iter().enumerate() {
+            let (control, chunk) = sequence
+                .strip_prefix("\x1b_G")
+                .unwrap()
+                .split_once(';')
+                .unwrap();
+            let last = i == sequences.len() - 1;
+            if i == 0 {
+                assert_eq!(control, "a=T,f=32,o=z,q=2,s=80,v=50,c=16,r=5,m=1");
+            } else {
+                assert_eq!(control, if last { "m=0" } else { "m=1" });
+            }
+            if last {
+                assert!(chunk.len() <= CHUNK_SIZE);
+            } else {
+                assert_eq!(chunk.len(), CHUNK_SIZE);
+            }
+            payload.push_str(chunk);
+        }
+
+        let rgba = image
+            .pixels
+            .iter()
+            .flat_map(|&[r, g, b]| [r, g, b, u8::MAX])
+            .collect::<Vec<_>>();
+        assert_eq!(payload, base64::encode(&png::zlib(&rgba)));
+    }
+}
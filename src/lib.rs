@@ -1,8 +1,21 @@
+pub mod base64;
 pub mod cli;
 pub mod color;
 pub mod flag;
+pub mod font;
+pub mod iterm;
+pub mod kitty;
 pub mod odd;
 pub mod overlay;
+pub mod paint;
+pub mod palette;
+pub mod png;
+pub mod render;
+pub mod screen;
+pub mod sixel;
+pub mod slideshow;
+pub mod theme;
+pub mod watch;
 
 pub mod term {
     use std::env::var;
@@ -13,4 +26,27 @@ pub mod term {
             lowercased == "truecolor" || lowercased == "24bit"
         })
     }
+
+    pub fn xterm256() -> bool {
+        var("TERM").is_ok_and(|val| val.contains("256color"))
+    }
+
+    pub fn kitty() -> bool {
+        var("TERM").is_ok_and(|val| val == "xterm-kitty") || var("KITTY_WINDOW_ID").is_ok()
+    }
+
+    /// Whether the terminal supports the inline images of iTerm2
+    pub fn iterm() -> bool {
+        var("TERM_PROGRAM").is_ok_and(|val| val == "iTerm.app" || val == "WezTerm")
+            || var("LC_TERMINAL").is_ok_and(|val| val == "iTerm2")
+    }
+
+    /// See <https://no-color.org>
+    pub fn no_color() -> bool {
+        var("NO_COLOR").is_ok_and(|val| !val.is_empty())
+    }
+
+    pub fn force_color() -> bool {
+        var("CLICOLOR_FORCE").is_ok_and(|val| !val.is_empty() && val != "0")
+    }
 }
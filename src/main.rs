@@ -1,18 +1,40 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use jiman::{
-    cli::{Cli, Command, PrintCli},
-    color::{AnsiCode, RESET},
-    flag::Flag,
+    cli::{
+        Animation, BannerCli, Cli, Command, Format, PaintCli, PaletteCli, PrintCli, Resolution,
+        SlideshowCli, ThemeCli,
+    },
+    flag::{Flag, custom},
+    iterm, kitty,
     overlay::Size,
+    paint, palette, png,
+    render::{
+        self,
+        raster::Image,
+        wave::{self, Timing},
+    },
+    sixel, slideshow,
+    theme::Theme,
+    watch,
+};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::{
+    fs::File,
+    io::{self, IsTerminal, Write},
+    num::NonZero,
+    path::Path,
+    sync::{Arc, atomic::AtomicBool},
 };
-use std::fmt::Write;
 
 fn main() {
+    load_custom_flags();
     let Cli { command } = Cli::parse();
 
     match command {
         Command::List { aliases } => {
-            for (variant, alts) in Flag::VALUES {
+            for flag in Flag::value_variants() {
+                let variant = flag.name();
+                let alts = flag.aliases();
                 let alts = (aliases && !alts.is_empty()).then(|| alts.join(", "));
                 match alts {
                     Some(alts) => {
@@ -25,6 +47,11 @@ fn main() {
             }
         }
         Command::Print(cli) => print_handler(cli),
+        Command::Slideshow(cli) => slideshow_handler(cli),
+        Command::Paint(cli) => paint_handler(cli),
+        Command::Banner(cli) => banner_handler(cli),
+        Command::Theme(cli) => theme_handler(cli),
+        Command::Palette(cli) => palette_handler(cli),
         #[cfg(feature = "complete")]
         Command::Complete { shell } => {
             use clap::CommandFactory;
@@ -35,50 +62,316 @@ fn main() {
     }
 }
 
-fn print_handler(cli: PrintCli) {
+fn load_custom_flags() {
+    let Some(dir) = custom::flags_dir() else {
+        return;
+    };
+
+    let flags = custom::load_dir(&dir)
+        .into_iter()
+        .filter_map(|(path, flag)| {
+            flag.inspect_err(|err| {
+                eprintln!("Skipping the flag in {}: {err}", path.display());
+            })
+            .ok()
+        })
+        .collect();
+
+    for flag in custom::register(flags) {
+        eprintln!(
+            "Skipping the flag {}: its name or an alias is already used",
+            flag.name
+        );
+    }
+}
+
+/// The size of the flag, in watch mode it fills the terminal by default
+fn print_size(cli: &PrintCli) -> Size {
+    let terminal = term_size::dimensions_stdout();
+
+    let height = cli
+        .shape
+        .height
+        .clone()
+        .and_then(|h| h.absolute_height())
+        .map(|h| h.get())
+        .or_else(|| terminal.filter(|_| cli.watch).map(|(_, h)| h))
+        .unwrap_or(15);
+
     let width = cli
+        .shape
         .width
         .clone()
         .and_then(|w| w.absolute_width())
         .map(|w| w.get())
         .or_else(|| {
-            let (wid, _) = term_size::dimensions_stdout()?;
-            Some(wid.min(71))
+            // Charachters are about twice as high as wide
+            let Resolution {
+                width: px_width,
+                height: px_height,
+            } = cli.pixels?;
+            Some((height * 2 * px_width.get() / px_height.get()).max(1))
+        })
+        .or_else(|| {
+            let (wid, _) = terminal?;
+            Some(if cli.watch { wid } else { wid.min(71) })
         })
         .unwrap_or(71);
 
+    Size::new(height, width)
+}
+
+/// A flag which is set once the process is asked to stop with Ctrl-C or `SIGTERM`
+fn stop_flag() -> Arc<AtomicBool> {
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [SIGINT, SIGTERM] {
+        if let Err(err) = signal_hook::flag::register(signal, Arc::clone(&stop)) {
+            eprintln!("Failed to handle signals: {err}");
+            std::process::exit(1);
+        }
+    }
+    stop
+}
+
+fn print_handler(cli: PrintCli) {
+    let size = print_size(&cli);
+    let is_terminal = cli.output.is_none() && io::stdout().is_terminal();
+    let mode = cli.color.mode(is_terminal);
+
+    if let Some(Animation::Wave) = cli.animate {
+        if !matches!(cli.format, None | Some(Format::Ansi)) {
+            eprintln!("Only the ansi format can be animated");
+            std::process::exit(2);
+        }
+
+        let stop = stop_flag();
+        let timing = Timing {
+            fps: cli.fps,
+            duration: cli.duration,
+        };
+        let result = open_output(cli.output.as_deref()).and_then(|mut out| {
+            wave::animate(
+                &mut out,
+                cli.flag,
+                size,
+                cli.shape.slope,
+                mode,
+                timing,
+                &stop,
+            )
+        });
+        return exit_on_error(result);
+    }
+
+    let transform = cli.transform();
+    if !transform.is_identity() && matches!(cli.format, Some(Format::HalfBlock)) {
+        eprintln!("The half-block format can't be rotated or mirrored");
+        std::process::exit(2);
+    }
+
+    if cli.watch {
+        let format = cli.format.unwrap_or(Format::Ansi);
+        if !matches!(format, Format::Ansi | Format::HalfBlock) {
+            eprintln!("Only the ansi and half-block formats can be watched");
+            std::process::exit(2);
+        }
+
+        let stop = stop_flag();
+        let result = watch::run(
+            || print_size(&cli),
+            |out, size| match format {
+                Format::HalfBlock => {
+                    render::half_block::render(out, cli.flag, size, cli.shape.slope, mode)
+                }
+                _ => render::render(out, cli.flag, size, cli.shape.slope, mode, transform),
+            },
+            &stop,
+        );
+        return exit_on_error(result);
+    }
+
+    let result = open_output(cli.output.as_deref()).and_then(|mut out| {
+        match cli.format.unwrap_or_else(|| Format::detect(is_terminal)) {
+            Format::Ansi => {
+                render::render(&mut out, cli.flag, size, cli.shape.slope, mode, transform)
+            }
+            Format::HalfBlock => {
+                render::half_block::render(&mut out, cli.flag, size, cli.shape.slope, mode)
+            }
+            Format::Svg => {
+                render::svg::render(&mut out, cli.flag, size, cli.shape.slope, transform)
+            }
+            Format::Png => png::encode(&mut out, &rasterize(&cli, size)),
+            Format::Sixel => sixel::encode(&mut out, &rasterize(&cli, size)),
+            Format::Kitty => kitty::encode(
+                &mut out,
+                &rasterize(&cli, size),
+                transform.image_cells(size),
+            ),
+            Format::Iterm => iterm::encode(
+                &mut out,
+                &rasterize(&cli, size),
+                transform.image_cells(size),
+            ),
+        }?;
+        out.flush()
+    });
+
+    exit_on_error(result);
+}
+
+fn exit_on_error(result: io::Result<()>) {
+    match result {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            eprintln!("Failed to print the flag: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn slideshow_handler(cli: SlideshowCli) {
+    let terminal = term_size::dimensions_stdout();
+    let width = cli
+        .shape
+        .width
+        .and_then(|w| w.absolute_width())
+        .map(|w| w.get())
+        .or_else(|| terminal.map(|(w, _)| w))
+        .unwrap_or(71);
+    // One line is left for the name of the flag
     let height = cli
+        .shape
         .height
         .and_then(|h| h.absolute_height())
-        .map_or(15, |v| v.get());
-
-    let stripes = cli.flag.stripes();
-    let stripe_height = height / stripes.len();
-    let height = stripe_height * stripes.len();
-    let size = Size::new(height, width);
-
-    let overlays = cli.flag.overlays(cli.slope, size);
-
-    for y in 0..height {
-        let stripe = &stripes[y / stripe_height];
-        let line = (0..width).fold(String::new(), |mut line, x| {
-            let Some((index, overlay, ch)) = overlays.iter().enumerate().find_map(|(i, ov)| {
-                let char = ov.at_pos(x, y, size)?;
-                Some((i, ov, char))
-            }) else {
-                write!(line, "{} ", stripe.bg()).expect("Writing to a String can't fail");
-                return line;
-            };
-            let fg = overlay.foreground().fg();
-            let bg = overlays
-                .iter()
-                .skip(index + 1)
-                .find(|overlay| overlay.at_pos(x, y, size).is_some())
-                .map_or_else(|| stripe.bg(), |overlay| overlay.foreground().bg());
-
-            write!(line, "{bg}{fg}{ch}").expect("Writing to a String can't fail");
-            line
-        });
-        println!("{line}{RESET}");
+        .map(|h| h.get())
+        .or_else(|| terminal.map(|(_, h)| h.saturating_sub(1)))
+        .unwrap_or(15);
+
+    let flags = if cli.flags.is_empty() {
+        Flag::value_variants().to_vec()
+    } else {
+        cli.flags
+    };
+
+    let stop = stop_flag();
+    let options = slideshow::Options {
+        size: Size::new(height, width),
+        slope: cli.shape.slope,
+        mode: cli.color.mode(io::stdout().is_terminal()),
+        interval: cli.seconds,
+        transition: cli.transition,
+    };
+    if let Err(err) = slideshow::run(&flags, options, &stop) {
+        eprintln!("Failed to show the slideshow: {err}");
+        std::process::exit(1);
     }
 }
+
+fn paint_handler(cli: PaintCli) {
+    // Heavier stripes are repeated, so they cover more lines or columns
+    let stripes = cli
+        .flag
+        .stripes()
+        .into_iter()
+        .zip(cli.flag.weights())
+        .flat_map(|(stripe, weight)| std::iter::repeat_n(stripe, weight))
+        .collect::<Vec<_>>();
+    let options = paint::Options {
+        direction: cli.direction,
+        band: cli.band.unwrap_or(cli.direction.default_band()),
+        foreground_only: cli.foreground_only,
+        mode: cli.color.mode(io::stdout().is_terminal()),
+    };
+    let result = paint::paint(
+        io::stdin().lock(),
+        &mut io::stdout().lock(),
+        &stripes,
+        options,
+    );
+    exit_on_error(result);
+}
+
+fn banner_handler(cli: BannerCli) {
+    let text = cli.text.join(" ");
+    // Without a width the text keeps its natural size as long as it fits into the terminal
+    let width = cli
+        .shape
+        .width
+        .and_then(|w| w.absolute_width())
+        .or_else(|| {
+            let natural = render::banner::natural_width(&text);
+            let terminal = term_size::dimensions_stdout().map_or(natural, |(w, _)| w);
+            NonZero::new(natural.min(terminal))
+        })
+        .unwrap_or(NonZero::<usize>::MIN);
+
+    let height = cli.shape.height.and_then(|h| h.absolute_height());
+
+    let mode = cli.color.mode(io::stdout().is_terminal());
+    let mut out = io::stdout().lock();
+    let result = render::banner::render(
+        &mut out,
+        cli.flag,
+        &text,
+        width,
+        height,
+        cli.shape.slope,
+        mode,
+    );
+    exit_on_error(result);
+}
+
+fn theme_handler(cli: ThemeCli) {
+    let theme = Theme::new(cli.flag);
+    let result = open_output(cli.output.as_deref()).and_then(|mut out| {
+        theme.write(&mut out, cli.target)?;
+        out.flush()
+    });
+    exit_on_error(result);
+}
+
+fn palette_handler(cli: PaletteCli) {
+    let result = open_output(cli.output.as_deref()).and_then(|mut out| {
+        palette::write(&mut out, cli.flag, cli.format)?;
+        out.flush()
+    });
+    exit_on_error(result);
+}
+
+/// Rasterizes and transforms the flag, by default every charachter is 10x20 pixels large.
+///
+/// The resolution is the one of the transformed image.
+fn rasterize(cli: &PrintCli, size: Size) -> Image {
+    let transform = cli.transform();
+    let pixels = cli.pixels.map_or_else(
+        || (size.width * 10, size.height * 20),
+        |res| {
+            // Only the transformed image has the requested resolution
+            let (width, height) = (res.width.get(), res.height.get());
+            if transform.is_quarter_turn() {
+                (height, width)
+            } else {
+                (width, height)
+            }
+        },
+    );
+    let pixels = (
+        NonZero::new(pixels.0).unwrap_or(NonZero::<usize>::MIN),
+        NonZero::new(pixels.1).unwrap_or(NonZero::<usize>::MIN),
+    );
+    transform.image(render::raster::rasterize(
+        cli.flag,
+        size,
+        cli.shape.slope,
+        pixels,
+    ))
+}
+
+fn open_output(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(io::BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    })
+}
@@ -95,6 +95,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::unnecessary_cast)]
     fn test_odd() {
         assert_eq!(Odd::<u32>::new(3), Some(Odd(3 as u32)));
         assert_eq!(Odd::<u32>::new(2), None);
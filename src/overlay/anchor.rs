@@ -0,0 +1,218 @@
+//! Placing overlays relative to the edges of the flag.
+//!
+//! The hoist is the left edge of a flag, the fly the right one.
+
+use super::{Coord, Point, Size};
+use crate::render::transform::{Mirror, Rotation, Transform};
+use serde::{Deserialize, Deserializer, de::Error as _};
+use std::str::FromStr;
+
+/// The edge or the center of the flag an overlay is placed at
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Anchor {
+    /// Touching the left edge, centered vertically
+    Hoist,
+    /// Touching the right edge, centered vertically
+    Fly,
+    /// Touching the top edge, centered horizontally
+    Top,
+    /// Touching the bottom edge, centered horizontally
+    Bottom,
+    #[default]
+    Center,
+}
+
+/// A distance in charachter cells, positive values move to the right or down
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Offset {
+    Cells(isize),
+    /// Percent of the width or height of the flag
+    Percent(f32),
+}
+
+impl Default for Offset {
+    fn default() -> Self {
+        Self::Cells(0)
+    }
+}
+
+impl Offset {
+    /// The offset in cells along an axis which is `length` cells long
+    pub fn cells(self, length: usize) -> isize {
+        match self {
+            Self::Cells(cells) => cells,
+            Self::Percent(percent) => (length as f32 * percent / 100.0).round() as isize,
+        }
+    }
+}
+
+impl FromStr for Offset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_suffix('%') {
+            Some(percent) => percent
+                .trim()
+                .parse()
+                .map(Self::Percent)
+                .map_err(|_| format!("Invalid percentage: {s}")),
+            None => s
+                .parse()
+                .map(Self::Cells)
+                .map_err(|_| format!("Invalid offset: {s}")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Offset {
+    /// Either a number of cells or a string like `"-3"` or `"25%"`
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Cells(isize),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Cells(cells) => Ok(Self::Cells(cells)),
+            Raw::Text(text) => text.parse().map_err(D::Error::custom),
+        }
+    }
+}
+
+/// Where an emblem is placed, the offsets are applied after anchoring it
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct Placement {
+    #[serde(default)]
+    pub anchor: Anchor,
+    #[serde(default)]
+    pub x: Offset,
+    #[serde(default)]
+    pub y: Offset,
+}
+
+impl Placement {
+    pub const fn new(anchor: Anchor, x: Offset, y: Offset) -> Self {
+        Self { anchor, x, y }
+    }
+
+    /// The center cell of an emblem which covers `extent` cells on a flag of `size`.
+    ///
+    /// Emblems pushed past the top or left edge stick to it.
+    pub fn center(&self, size: Size, extent: Size) -> Coord {
+        let (center_x, center_y) = size.center();
+        let left = extent.width / 2;
+        let top = extent.height / 2;
+        let right = size.width.saturating_sub(extent.width - left);
+        let bottom = size.height.saturating_sub(extent.height - top);
+
+        let (x, y) = match self.anchor {
+            Anchor::Hoist => (left, center_y),
+            Anchor::Fly => (right, center_y),
+            Anchor::Top => (center_x, top),
+            Anchor::Bottom => (center_x, bottom),
+            Anchor::Center => (center_x, center_y),
+        };
+
+        (
+            x.saturating_add_signed(self.x.cells(size.width)),
+            y.saturating_add_signed(self.y.cells(size.height)),
+        )
+    }
+}
+
+/// The edge a triangle or chevron points in from
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Side {
+    #[default]
+    Hoist,
+    Fly,
+}
+
+impl TryFrom<Anchor> for Side {
+    type Error = String;
+
+    fn try_from(anchor: Anchor) -> Result<Self, Self::Error> {
+        match anchor {
+            Anchor::Hoist => Ok(Self::Hoist),
+            Anchor::Fly => Ok(Self::Fly),
+            _ => Err(String::from(
+                "Triangles and chevrons can only be anchored at the hoist or the fly",
+            )),
+        }
+    }
+}
+
+impl Side {
+    /// The column of an overlay pointing in from the hoist that is drawn at `col`
+    pub const fn column(self, col: usize, size: Size) -> Option<usize> {
+        match self {
+            Self::Hoist => Some(col),
+            Self::Fly if col < size.width => Some(size.width - 1 - col),
+            Self::Fly => None,
+        }
+    }
+
+    /// Flips block charachters of an overlay pointing in from the hoist if needed
+    pub fn glyph(self, char: char) -> char {
+        match self {
+            Self::Hoist => char,
+            Self::Fly => Transform {
+                rotation: Rotation::None,
+                mirror: Some(Mirror::Horizontal),
+            }
+            .glyph(char),
+        }
+    }
+
+    /// Flips the outline of an overlay pointing in from the hoist if needed
+    pub fn points(self, points: Vec<Point>, size: Size) -> Vec<Point> {
+        match self {
+            Self::Hoist => points,
+            Self::Fly => points
+                .into_iter()
+                .map(|(x, y)| (size.width as f32 - x, y))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placement() {
+        let size = Size::new(15, 71);
+        let extent = Size::new(5, 9);
+        let at = |anchor, x, y| Placement::new(anchor, x, y).center(size, extent);
+
+        let zero = Offset::default();
+
+        assert_eq!(at(Anchor::Center, zero, zero), (35, 7));
+        assert_eq!(at(Anchor::Hoist, zero, zero), (4, 7));
+        assert_eq!(at(Anchor::Fly, zero, zero), (66, 7));
+        assert_eq!(at(Anchor::Top, zero, zero), (35, 2));
+        assert_eq!(at(Anchor::Bottom, zero, zero), (35, 12));
+        assert_eq!(
+            at(Anchor::Fly, Offset::Cells(-2), Offset::Cells(1)),
+            (64, 8)
+        );
+        assert_eq!(
+            at(Anchor::Hoist, Offset::Percent(10.0), Offset::Cells(-20)),
+            (11, 0)
+        );
+
+        assert_eq!("25%".parse(), Ok(Offset::Percent(25.0)));
+        assert_eq!("-3".parse(), Ok(Offset::Cells(-3)));
+        assert!("left".parse::<Offset>().is_err());
+
+        assert_eq!(Side::Fly.column(0, size), Some(70));
+        assert_eq!(Side::Fly.column(71, size), None);
+        assert_eq!(Side::Fly.glyph('▙'), '▟');
+        assert_eq!(Side::Fly.glyph('▌'), '▐');
+    }
+}
@@ -1,5 +1,21 @@
-use super::Overlay;
-use crate::color::Color;
+use super::{Overlay, Shape};
+use crate::{
+    color::Color,
+    font::{self, GLYPH_HEIGHT, GLYPH_WIDTH},
+};
+
+/// Whether a point inside of a cell (both coordinates in `0.0..1.0`) is covered by `char`.
+///
+/// Charachters without a glyph in the [`font`] fill the entire cell.
+pub(crate) fn glyph_contains(char: char, (x, y): (f32, f32)) -> bool {
+    let Some(glyph) = font::lookup(char) else {
+        return true;
+    };
+
+    let col = ((x * GLYPH_WIDTH as f32) as usize).min(GLYPH_WIDTH - 1);
+    let row = ((y * GLYPH_HEIGHT as f32) as usize).min(GLYPH_HEIGHT - 1);
+    font::is_set(&glyph, col, row)
+}
 
 pub struct OverlayCharachter {
     char: char,
@@ -8,12 +24,12 @@ pub struct OverlayCharachter {
 }
 
 impl OverlayCharachter {
+    pub const fn new(char: char, color: Color, coord: (usize, usize)) -> Self {
+        Self { char, color, coord }
+    }
+
     pub const fn new_centered(char: char, color: Color, size: super::Size) -> Self {
-        Self {
-            char,
-            color,
-            coord: size.center(),
-        }
+        Self::new(char, color, size.center())
     }
 }
 
@@ -27,4 +43,11 @@ impl Overlay for OverlayCharachter {
     fn at_pos(&self, col: usize, row: usize, _: super::Size) -> Option<char> {
         (self.coord == (col, row)).then_some(self.char)
     }
+
+    fn shape(&self, _: super::Size) -> Shape {
+        Shape::Text {
+            center: (self.coord.0 as f32 + 0.5, self.coord.1 as f32 + 0.5),
+            char: self.char,
+        }
+    }
 }
@@ -0,0 +1,97 @@
+use super::{Overlay, Shape, Size, anchor::Side, triangle::Triangle};
+use crate::color::Color;
+use std::num::NonZero;
+
+/// A band pointing to the right, like the [`Triangle`] it grows from the left side but leaves out
+/// the first `insert` columns.
+pub struct Chevron {
+    inner: Triangle,
+    outer: Triangle,
+    color: Color,
+    side: Side,
+}
+
+impl Chevron {
+    pub const fn new(
+        padding: usize,
+        insert: usize,
+        width: usize,
+        slope: NonZero<usize>,
+        color: Color,
+    ) -> Self {
+        Self {
+            inner: Triangle::new(padding, insert, slope, color),
+            outer: Triangle::new(padding, insert + width, slope, color),
+            color,
+            side: Side::Hoist,
+        }
+    }
+
+    /// Points the chevron in from another edge, `insert` is counted from that edge
+    pub const fn with_side(mut self, side: Side) -> Self {
+        self.side = side;
+        self
+    }
+}
+
+impl Overlay for Chevron {
+    type Foreground = Color;
+
+    fn foreground(&self) -> Self::Foreground {
+        self.color
+    }
+
+    fn at_pos(&self, col: usize, row: usize, size: Size) -> Option<char> {
+        // Both triangles point in from the hoist, the result is flipped afterwards
+        let col = self.side.column(col, size)?;
+        // The cells on the inner edge show the part the inner triangle leaves empty
+        let char = match self.inner.at_pos(col, row, size) {
+            None => self.outer.at_pos(col, row, size),
+            Some('▙') => Some('▝'),
+            Some('▛') => Some('▗'),
+            Some('▌') => Some('▐'),
+            Some(_) => None,
+        };
+        char.map(|char| self.side.glyph(char))
+    }
+
+    fn shape(&self, size: Size) -> Shape {
+        let (Shape::Polygon(inner), Shape::Polygon(outer)) =
+            (self.inner.shape(size), self.outer.shape(size))
+        else {
+            unreachable!("Triangles are always polygons")
+        };
+
+        // Both triangles share the two points on the left side, which are skipped
+        let points = outer[1..4]
+            .iter()
+            .chain(inner[1..4].iter().rev())
+            .copied()
+            .collect();
+        Shape::Polygon(self.side.points(points, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chevron() {
+        let size = Size::new(7, 20);
+        let chevron = Chevron::new(0, 2, 3, NonZero::<usize>::MIN, Color::BLACK);
+        let row = |row: usize| {
+            (0..8)
+                .map(|col| chevron.at_pos(col, row, size).unwrap_or(' '))
+                .collect::<String>()
+        };
+
+        // Left of the inner edge the chevron is open, right of the outer edge the stripes show
+        assert_eq!(row(0), "  ▝██▙  ");
+        assert_eq!(row(1), "   ▝██▙ ");
+        assert_eq!(row(3), "     ▐██");
+        assert_eq!(row(5), "   ▗██▛ ");
+        assert_eq!(row(6), "  ▗██▛  ");
+        assert_eq!(chevron.at_pos(9, 3, size), None);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Coord, Overlay, Size};
+use super::{CELL_ASPECT, Coord, Overlay, Shape, Size};
 use crate::{color::Color, odd::Odd};
 use std::num::NonZero;
 
@@ -24,6 +24,20 @@ impl Circle {
         }
     }
 
+    /// How many rows each of the diagonal parts of the outline spans, two fifths of the diameter
+    const fn diagonal_height(diameter: usize) -> usize {
+        // Dividing first keeps huge diameters from overflowing
+        diameter / 5 * 2 + diameter % 5 * 2 / 5
+    }
+
+    /// The cells covered by a circle of `diameter`, it is wider than high since cells are about
+    /// twice as tall as they are wide
+    pub const fn extent(diameter: Odd<usize>) -> Size {
+        let diameter = diameter.value();
+        let diagonal_height = Self::diagonal_height(diameter);
+        Size::new(diameter, diameter.saturating_add(diagonal_height * 2))
+    }
+
     fn offset_from_center(&self, (col, row): Coord) -> Option<(isize, isize)> {
         let center = (
             isize::try_from(self.center.0).ok()?,
@@ -49,10 +63,10 @@ impl Overlay for Circle {
         let radius = (diameter - 1) / 2;
         let radius = radius.try_into().ok()?;
 
-        let diagonal_height = (diameter * 2) / 5;
+        let diagonal_height = Self::diagonal_height(diameter);
         // Mathemathically proven to not panic
         let vertical_height = Odd::<usize>::new_panics(diameter - 2 * diagonal_height);
-        let total_width = diameter + diagonal_height * 2;
+        let total_width = Self::extent(self.diameter).width;
 
         let offset = self.offset_from_center((col, row))?;
 
@@ -65,7 +79,7 @@ impl Overlay for Circle {
         }
 
         let vertical_radius = isize::try_from((vertical_height - 1) / 2).ok()?;
-        let vertical_column = ((total_width - 1) / 2).try_into().ok()?;
+        let vertical_column = isize::try_from((total_width - 1) / 2).ok()?;
 
         if (offset.0 == vertical_column) && (offset.1.abs() <= vertical_radius) {
             return Some(VERTICAL_LEFT);
@@ -92,7 +106,7 @@ impl Overlay for Circle {
         let added_offset =
             offset_from_horizontal_line.abs().get() + offset_from_vertical_line.abs().get() - 1;
 
-        (added_offset == diagonal_height.try_into().ok()?).then(|| {
+        (added_offset == isize::try_from(diagonal_height).ok()?).then(|| {
             let is_top_right = offset_from_horizontal_line.is_positive()
                 && offset_from_vertical_line.is_positive();
             let is_bottom_left = offset_from_horizontal_line.is_negative()
@@ -105,4 +119,17 @@ impl Overlay for Circle {
             }
         })
     }
+
+    fn shape(&self, _: Size) -> Shape {
+        // A perfect circle with the same height as the charachters, its outline is drawn with
+        // half blocks so it is half a cell thick
+        let radius = ((self.diameter.value() - 1) / 2) as f32 + 0.5;
+        let outer = (radius * CELL_ASPECT, radius);
+
+        Shape::Ring {
+            center: (self.center.0 as f32 + 0.5, self.center.1 as f32 + 0.5),
+            outer,
+            inner: (outer.0 - 0.5 * CELL_ASPECT, outer.1 - 0.5),
+        }
+    }
 }
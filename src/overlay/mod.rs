@@ -1,5 +1,8 @@
+pub mod anchor;
 pub mod charachter;
+pub mod chevron;
 pub mod circle;
+pub mod star;
 pub mod triangle;
 
 use crate::color::AnsiCode;
@@ -10,6 +13,72 @@ pub trait Overlay {
     fn foreground(&self) -> Self::Foreground;
 
     fn at_pos(&self, col: usize, row: usize, size: Size) -> Option<char>;
+
+    /// The vector outline of the overlay, measured in charachter cells
+    fn shape(&self, size: Size) -> Shape;
+}
+
+/// How many times a charachter cell is higher than wide
+pub const CELL_ASPECT: f32 = 2.0;
+
+/// A point measured in charachter cells, `(0.0, 0.0)` is the top left corner of the first cell
+pub type Point = (f32, f32);
+
+pub enum Shape {
+    Polygon(Vec<Point>),
+    /// The area between two ellipses sharing the same center
+    Ring {
+        center: Point,
+        outer: (f32, f32),
+        inner: (f32, f32),
+    },
+    Text {
+        center: Point,
+        char: char,
+    },
+}
+
+impl Shape {
+    /// Whether the point lies within the polygon with the corners `points`
+    pub fn contains_polygon(points: &[Point], (x, y): Point) -> bool {
+        // Even-odd rule, a ray is cast to the right and the crossed edges are counted
+        let mut inside = false;
+        let mut prev = points.last().copied().unwrap_or_default();
+        for &(px, py) in points {
+            let (qx, qy) = prev;
+            if (py > y) != (qy > y) && x < (qx - px) * (y - py) / (qy - py) + px {
+                inside = !inside;
+            }
+            prev = (px, py);
+        }
+        inside
+    }
+
+    /// Whether the point lies within the shape
+    pub fn contains(&self, (x, y): Point) -> bool {
+        match self {
+            Self::Polygon(points) => Self::contains_polygon(points, (x, y)),
+            Self::Ring {
+                center,
+                outer,
+                inner,
+            } => {
+                let in_ellipse = |(rx, ry): (f32, f32)| {
+                    let dx = (x - center.0) / rx;
+                    let dy = (y - center.1) / ry;
+                    dx * dx + dy * dy <= 1.0
+                };
+                in_ellipse(*outer) && !in_ellipse(*inner)
+            }
+            Self::Text { center, char } => {
+                let dx = x - center.0 + 0.5;
+                let dy = y - center.1 + 0.5;
+                (0.0..1.0).contains(&dx)
+                    && (0.0..1.0).contains(&dy)
+                    && charachter::glyph_contains(*char, (dx, dy))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
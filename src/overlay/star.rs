@@ -0,0 +1,109 @@
+use super::{CELL_ASPECT, Coord, Overlay, Point, Shape, Size};
+use crate::color::Color;
+use std::{f32::consts::PI, num::NonZero};
+
+const UPPER_HALF: char = '▀';
+const LOWER_HALF: char = '▄';
+const FULL: char = '█';
+
+/// A star whose first point faces up, the radii are measured in rows.
+///
+/// Every cell is split into an upper and a lower half, which are about as wide as high, so the
+/// star keeps its proportions.
+pub struct Star {
+    /// The tips and the notches between them, alternating and going clockwise
+    outline: Vec<Point>,
+    color: Color,
+}
+
+impl Star {
+    pub fn new(
+        center: Coord,
+        points: NonZero<usize>,
+        outer: f32,
+        inner: f32,
+        color: Color,
+    ) -> Self {
+        let center = (center.0 as f32 + 0.5, center.1 as f32 + 0.5);
+        let outline = (0..points.get() * 2)
+            .map(|i| {
+                let radius = if i % 2 == 0 { outer } else { inner };
+                let angle = i as f32 * PI / points.get() as f32;
+                (
+                    center.0 + radius * angle.sin() * CELL_ASPECT,
+                    center.1 - radius * angle.cos(),
+                )
+            })
+            .collect();
+
+        Self { outline, color }
+    }
+
+    /// The cells covered by a star with an outer radius of `outer` rows
+    pub fn extent(outer: f32) -> Size {
+        let height = (outer * 2.0).ceil() as usize;
+        Size::new(height, (outer * 2.0 * CELL_ASPECT).ceil() as usize)
+    }
+
+    /// Whether the point lies within the star
+    fn contains(&self, point: Point) -> bool {
+        Shape::contains_polygon(&self.outline, point)
+    }
+}
+
+impl Overlay for Star {
+    type Foreground = Color;
+
+    fn foreground(&self) -> Self::Foreground {
+        self.color
+    }
+
+    fn at_pos(&self, col: usize, row: usize, _: Size) -> Option<char> {
+        let x = col as f32 + 0.5;
+        let upper = self.contains((x, row as f32 + 0.25));
+        let lower = self.contains((x, row as f32 + 0.75));
+
+        match (upper, lower) {
+            (true, true) => Some(FULL),
+            (true, false) => Some(UPPER_HALF),
+            (false, true) => Some(LOWER_HALF),
+            (false, false) => None,
+        }
+    }
+
+    fn shape(&self, _: Size) -> Shape {
+        Shape::Polygon(self.outline.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_star() {
+        let size = Size::new(15, 71);
+        let points = NonZero::new(5).unwrap();
+        let star = Star::new((35, 7), points, 6.0, 2.5, Color::BLACK);
+
+        let extent = Star::extent(6.0);
+        assert_eq!((extent.height, extent.width), (12, 24));
+        assert_eq!(star.at_pos(35, 7, size), Some(FULL));
+        // The top point ends between the halves of the cell six rows above the center
+        assert_eq!(star.at_pos(35, 1, size), Some(LOWER_HALF));
+        assert_eq!(star.at_pos(35, 0, size), None);
+        // Between the two lower points
+        assert_eq!(star.at_pos(35, 12, size), None);
+        assert_eq!(star.at_pos(47, 7, size), None);
+
+        // The star is mirrored along its vertical axis
+        for row in 0..size.height {
+            for offset in 1..15 {
+                assert_eq!(
+                    star.at_pos(35 - offset, row, size),
+                    star.at_pos(35 + offset, row, size)
+                );
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Coord, Overlay, Size};
+use super::{Coord, Overlay, Shape, Size, anchor::Side};
 use crate::color::Color;
 use std::num::NonZero;
 
@@ -7,6 +7,7 @@ pub struct Triangle {
     insert: usize,
     slope: NonZero<usize>,
     color: Color,
+    side: Side,
 }
 
 impl Triangle {
@@ -16,8 +17,15 @@ impl Triangle {
             insert,
             slope,
             color,
+            side: Side::Hoist,
         }
     }
+
+    /// Points the triangle in from another edge, `insert` is counted from that edge
+    pub const fn with_side(mut self, side: Side) -> Self {
+        self.side = side;
+        self
+    }
 }
 
 impl Overlay for Triangle {
@@ -33,6 +41,7 @@ impl Overlay for Triangle {
         if !(padding..size.height.saturating_sub(padding)).contains(&row) {
             return None;
         }
+        let col = self.side.column(col, size)?;
 
         TriangleChar::at_pos(
             size.height - padding * 2,
@@ -40,7 +49,24 @@ impl Overlay for Triangle {
             (col, row - padding),
             self.insert,
         )
-        .map(char::from)
+        .map(|char| self.side.glyph(char.into()))
+    }
+
+    fn shape(&self, size: Size) -> Shape {
+        let top = self.padding as f32;
+        let bottom = size.height.saturating_sub(self.padding) as f32;
+        let middle = (top + bottom) / 2.0;
+        let base = self.insert as f32 + 0.5;
+        let tip = base + self.slope.get() as f32 * (bottom - top) / 2.0;
+
+        let points = vec![
+            (0.0, top),
+            (base, top),
+            (tip, middle),
+            (base, bottom),
+            (0.0, bottom),
+        ];
+        Shape::Polygon(self.side.points(points, size))
     }
 }
 
@@ -0,0 +1,182 @@
+//! Recoloring text with the stripes of a flag.
+
+use crate::{
+    color::{AnsiColor, Color, ColorMode, RESET},
+    flag::FallbackedColor,
+};
+use clap::ValueEnum;
+use std::{
+    io::{self, BufRead},
+    num::NonZero,
+};
+use unicode_width::UnicodeWidthChar;
+
+const ESCAPE: char = '\x1b';
+const BELL: char = '\x07';
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Direction {
+    /// Every line gets the color of a stripe
+    Lines,
+    /// Every column gets the color of a stripe
+    Columns,
+    /// The stripes run diagonally from the top right to the bottom left
+    Diagonal,
+}
+
+impl Direction {
+    /// How many lines or columns a stripe covers if nothing else was requested
+    pub const fn default_band(self) -> NonZero<usize> {
+        match self {
+            Self::Lines => NonZero::<usize>::MIN,
+            Self::Columns | Self::Diagonal => unsafe { NonZero::new_unchecked(2) },
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Options {
+    pub direction: Direction,
+    /// How many lines or columns a single stripe covers
+    pub band: NonZero<usize>,
+    /// Only color the text instead of the background
+    pub foreground_only: bool,
+    pub mode: ColorMode,
+}
+
+/// Copies `input` into `out`, coloring every charachter with one of the `stripes`.
+///
+/// Escape sequences in the input are passed through unchanged and don't take up a column, wide
+/// charachters take up two.
+pub fn paint(
+    input: impl BufRead,
+    out: &mut impl io::Write,
+    stripes: &[FallbackedColor],
+    options: Options,
+) -> io::Result<()> {
+    if stripes.is_empty() || options.mode == ColorMode::None {
+        return copy(input, out);
+    }
+
+    for (row, line) in input.split(b'\n').enumerate() {
+        let line = line?;
+        let line = String::from_utf8_lossy(&line);
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+
+        let mut col = 0;
+        // The stripe the current colors were set for, escape sequences in the input may have
+        // changed them
+        let mut current = None;
+        let mut chars = line.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char == ESCAPE {
+                write!(out, "{char}")?;
+                pass_escape(&mut chars, out)?;
+                current = None;
+                continue;
+            }
+
+            let band = match options.direction {
+                Direction::Lines => row,
+                Direction::Columns => col,
+                Direction::Diagonal => row + col,
+            } / options.band.get();
+            let stripe = band % stripes.len();
+
+            if current != Some(stripe) {
+                write!(out, "{}", colors(&stripes[stripe], options))?;
+                current = Some(stripe);
+            }
+            write!(out, "{char}")?;
+            col += char.width().unwrap_or(0);
+        }
+
+        writeln!(out, "{RESET}")?;
+    }
+
+    Ok(())
+}
+
+/// The escape codes to write a charachter on `stripe`
+fn colors(stripe: &FallbackedColor, options: Options) -> String {
+    if options.foreground_only {
+        return stripe.fg_in(options.mode);
+    }
+
+    // Black or white text, whichever is more readable
+    let [r, g, b] = stripe.true_color().rgb().map(u32::from);
+    let luminance = 299 * r + 587 * g + 114 * b;
+    let text = if luminance > 150_000 {
+        FallbackedColor::new(Color::BLACK, AnsiColor::Black)
+    } else {
+        FallbackedColor::new(Color::WHITE, AnsiColor::White)
+    };
+
+    format!("{}{}", stripe.bg_in(options.mode), text.fg_in(options.mode))
+}
+
+/// Writes the rest of an escape sequence which started with [`ESCAPE`]
+fn pass_escape(
+    chars: &mut std::iter::Peekable<impl Iterator<Item = char>>,
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    match chars.next() {
+        // Control sequences end with a byte in the range `@` to `~`
+        Some('[') => {
+            write!(out, "[")?;
+            for char in chars.by_ref() {
+                write!(out, "{char}")?;
+                if ('@'..='~').contains(&char) {
+                    break;
+                }
+            }
+        }
+        // Operating system commands end with a bell or `ESC \`
+        Some(']') => {
+            write!(out, "]")?;
+            while let Some(char) = chars.next() {
+                write!(out, "{char}")?;
+                if char == BELL {
+                    break;
+                }
+                if char == ESCAPE && chars.next_if_eq(&'\\').is_some() {
+                    write!(out, "\\")?;
+                    break;
+                }
+            }
+        }
+        Some(char) => write!(out, "{char}")?,
+        None => {}
+    }
+    Ok(())
+}
+
+fn copy(mut input: impl BufRead, out: &mut impl io::Write) -> io::Result<()> {
+    io::copy(&mut input, out).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        let stripes = [
+            FallbackedColor::new(Color::BLACK, AnsiColor::Red),
+            FallbackedColor::new(Color::WHITE, AnsiColor::Blue),
+        ];
+        let options = Options {
+            direction: Direction::Columns,
+            band: NonZero::<usize>::MIN,
+            foreground_only: true,
+            mode: ColorMode::Ansi,
+        };
+
+        let mut out = Vec::new();
+        paint("a\x1b[1m界bc\n".as_bytes(), &mut out, &stripes, options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\x1b[31ma\x1b[1m\x1b[34m界b\x1b[31mc\x1b[0m\n"
+        );
+    }
+}
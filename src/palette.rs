@@ -0,0 +1,225 @@
+//! The colors of a flag for design tools and stylesheets.
+
+use crate::{color::Color, flag::Flag, overlay::Size};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{io, num::NonZero};
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Format {
+    /// One hex color per line followed by its name
+    Hex,
+    /// CSS custom properties
+    Css,
+    /// SCSS variables
+    Scss,
+    /// A GIMP palette, also understood by Inkscape and Krita
+    Gpl,
+    /// A JSON object with the name of the flag and its colors
+    Json,
+    /// An Adobe Swatch Exchange file
+    Ase,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Stripe,
+    Overlay,
+}
+
+/// A single color of a flag
+pub struct Entry {
+    pub kind: Kind,
+    /// The position among the colors of the same kind, starting at 1
+    pub index: usize,
+    pub color: Color,
+}
+
+impl Entry {
+    /// A name like `stripe-2`
+    pub fn name(&self) -> String {
+        let kind = match self.kind {
+            Kind::Stripe => "stripe",
+            Kind::Overlay => "overlay",
+        };
+        format!("{kind}-{}", self.index)
+    }
+}
+
+/// The stripes of `flag` from top to bottom followed by its overlays from the topmost one down.
+///
+/// Colors used multiple times are listed every time, so the order matches the flag.
+pub fn entries(flag: Flag) -> Vec<Entry> {
+    let stripes = flag.stripes().into_iter().map(|stripe| stripe.true_color());
+    // The colors of the overlays don't depend on the size
+    let overlays = flag
+        .overlays(NonZero::<usize>::MIN, Size::new(15, 71))
+        .into_iter()
+        .map(|overlay| overlay.foreground());
+
+    let stripes = stripes
+        .enumerate()
+        .map(|(i, color)| (Kind::Stripe, i, color));
+    let overlays = overlays
+        .enumerate()
+        .map(|(i, color)| (Kind::Overlay, i, color));
+    stripes
+        .chain(overlays)
+        .map(|(kind, i, color)| Entry {
+            kind,
+            index: i + 1,
+            color,
+        })
+        .collect()
+}
+
+/// The name of a flag for identifiers, e.g. `lgbtqia` for `Lgbtqia+`
+fn slug(name: &str) -> String {
+    let slug = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    slug.trim_matches('-').to_owned()
+}
+
+#[derive(Serialize)]
+struct JsonPalette<'a> {
+    name: &'a str,
+    colors: Vec<JsonEntry>,
+}
+
+#[derive(Serialize)]
+struct JsonEntry {
+    name: String,
+    kind: Kind,
+    hex: String,
+    rgb: [u8; 3],
+}
+
+/// Writes the colors of `flag` in `format`
+pub fn write(out: &mut impl io::Write, flag: Flag, format: Format) -> io::Result<()> {
+    let name = flag.name();
+    let slug = slug(name);
+    let entries = entries(flag);
+
+    match format {
+        Format::Hex => {
+            for entry in &entries {
+                writeln!(out, "{}\t{}", entry.color.hex(), entry.name())?;
+            }
+        }
+        Format::Css => {
+            writeln!(out, "/* {name} */")?;
+            writeln!(out, ":root {{")?;
+            for entry in &entries {
+                writeln!(out, "  --{slug}-{}: {};", entry.name(), entry.color.hex())?;
+            }
+            writeln!(out, "}}")?;
+        }
+        Format::Scss => {
+            writeln!(out, "// {name}")?;
+            for entry in &entries {
+                writeln!(out, "${slug}-{}: {};", entry.name(), entry.color.hex())?;
+            }
+        }
+        Format::Gpl => {
+            writeln!(out, "GIMP Palette")?;
+            writeln!(out, "Name: {name}")?;
+            writeln!(out, "#")?;
+            for entry in &entries {
+                let [r, g, b] = entry.color.rgb();
+                writeln!(out, "{r:3} {g:3} {b:3}\t{}", entry.name())?;
+            }
+        }
+        Format::Json => {
+            let palette = JsonPalette {
+                name,
+                colors: entries
+                    .iter()
+                    .map(|entry| JsonEntry {
+                        name: entry.name(),
+                        kind: entry.kind,
+                        hex: entry.color.hex(),
+                        rgb: entry.color.rgb(),
+                    })
+                    .collect(),
+            };
+            serde_json::to_writer_pretty(&mut *out, &palette)?;
+            writeln!(out)?;
+        }
+        Format::Ase => write_ase(out, name, &entries)?,
+    }
+
+    Ok(())
+}
+
+/// Writes an Adobe Swatch Exchange file with a group named after the flag
+fn write_ase(out: &mut impl io::Write, name: &str, entries: &[Entry]) -> io::Result<()> {
+    const GROUP_START: u16 = 0xc001;
+    const GROUP_END: u16 = 0xc002;
+    const COLOR: u16 = 0x0001;
+    /// Neither a global nor a spot color
+    const NORMAL: u16 = 2;
+
+    /// A length prefixed and null terminated UTF-16 string
+    fn string(s: &str) -> Vec<u8> {
+        let units = s.encode_utf16().chain([0]).collect::<Vec<_>>();
+        let len = u16::try_from(units.len()).unwrap_or(u16::MAX);
+        len.to_be_bytes()
+            .into_iter()
+            .chain(units.into_iter().flat_map(u16::to_be_bytes))
+            .collect()
+    }
+
+    let mut blocks = vec![(GROUP_START, string(name))];
+    for entry in entries {
+        let mut block = string(&entry.name());
+        block.extend(b"RGB ");
+        for channel in entry.color.rgb() {
+            block.extend((f32::from(channel) / 255.0).to_be_bytes());
+        }
+        block.extend(NORMAL.to_be_bytes());
+        blocks.push((COLOR, block));
+    }
+    blocks.push((GROUP_END, Vec::new()));
+
+    out.write_all(b"ASEF")?;
+    // Version 1.0
+    out.write_all(&[0, 1, 0, 0])?;
+    out.write_all(&(blocks.len() as u32).to_be_bytes())?;
+    for (kind, block) in blocks {
+        out.write_all(&kind.to_be_bytes())?;
+        out.write_all(&(block.len() as u32).to_be_bytes())?;
+        out.write_all(&block)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_palette() {
+        assert_eq!(slug("Lgbtqia+"), "lgbtqia");
+
+        let mut out = Vec::new();
+        write(&mut out, Flag::Intersex, Format::Scss).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "// Intersex\n$intersex-stripe-1: #fcdb04;\n$intersex-overlay-1: #892a88;\n"
+        );
+
+        let mut out = Vec::new();
+        write(&mut out, Flag::Intersex, Format::Ase).unwrap();
+        assert_eq!(&out[..12], b"ASEF\0\x01\0\0\0\0\0\x04");
+    }
+}
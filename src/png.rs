@@ -0,0 +1,356 @@
+//! A minimal PNG encoder.
+//!
+//! Every row is filtered with the previous one and the result is compressed using only
+//! run-length matches and the fixed huffman codes of DEFLATE. Flags consist of large areas of a
+//! single color, so this is good enough while not depending on a compression library.
+
+use crate::render::raster::Image;
+use std::io;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const BYTES_PER_PIXEL: usize = 3;
+
+/// The "Up" filter type, every byte is stored as the difference to the byte above it
+const FILTER_UP: u8 = 2;
+
+pub fn encode(out: &mut impl io::Write, image: &Image) -> io::Result<()> {
+    out.write_all(&SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(
+        &u32::try_from(image.width)
+            .map_err(invalid_size)?
+            .to_be_bytes(),
+    );
+    header.extend_from_slice(
+        &u32::try_from(image.height)
+            .map_err(invalid_size)?
+            .to_be_bytes(),
+    );
+    // Bit depth 8, truecolor, deflate, adaptive filtering, no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(out, b"IHDR", &header)?;
+
+    let stride = image.width * BYTES_PER_PIXEL;
+    let mut filtered = Vec::with_capacity((stride + 1) * image.height);
+    let mut previous = vec![0; stride];
+    for row in image.rows() {
+        let row = row.as_flattened();
+        filtered.push(FILTER_UP);
+        filtered.extend(row.iter().zip(&previous).map(|(a, b)| a.wrapping_sub(*b)));
+        previous.copy_from_slice(row);
+    }
+
+    write_chunk(out, b"IDAT", &zlib(&filtered))?;
+    write_chunk(out, b"IEND", &[])
+}
+
+fn invalid_size<E>(_: E) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "The image is too large for a PNG",
+    )
+}
+
+fn write_chunk(out: &mut impl io::Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(data.len()).map_err(invalid_size)?;
+    out.write_all(&len.to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+        while n < 256 {
+            let mut c = n as u32;
+            let mut k = 0;
+            while k < 8 {
+                c = if c & 1 == 1 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+            table[n] = c;
+            n += 1;
+        }
+        table
+    };
+
+    !bytes.into_iter().fold(u32::MAX, |crc, &byte| {
+        TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (a, b) = bytes.iter().fold((1, 0), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % MOD;
+        (a, (b + a) % MOD)
+    });
+    (b << 16) | a
+}
+
+pub(crate) fn zlib(data: &[u8]) -> Vec<u8> {
+    // Deflate with a 32K window, no preset dictionary
+    let mut out = vec![0x78, 0x01];
+    out.extend(deflate(data));
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// The distances checked for matches, the previous byte and the previous pixel
+const DISTANCES: [usize; 2] = [1, BYTES_PER_PIXEL];
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // A single final block using the fixed huffman codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let best = DISTANCES
+            .iter()
+            .filter(|&&distance| distance <= i)
+            .map(|&distance| {
+                let len = data[i..]
+                    .iter()
+                    .zip(&data[i - distance..])
+                    .take(MAX_MATCH)
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (len, distance)
+            })
+            .max_by_key(|(len, _)| *len);
+
+        match best {
+            Some((len, distance)) if len >= MIN_MATCH => {
+                bits.match_(len, distance);
+                i += len;
+            }
+            _ => {
+                bits.symbol(u16::from(data[i]));
+                i += 1;
+            }
+        }
+    }
+
+    // End of block
+    bits.symbol(256);
+    bits.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    len: u8,
+}
+
+impl BitWriter {
+    /// Writes the lowest `count` bits of `value`, least significant bit first
+    fn write(&mut self, value: u32, count: u8) {
+        self.current |= value << self.len;
+        self.len += count;
+        while self.len >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Huffman codes are packed starting with their most significant bit
+    fn code(&mut self, code: u32, count: u8) {
+        self.write(code.reverse_bits() >> (32 - count), count);
+    }
+
+    fn symbol(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn match_(&mut self, len: usize, distance: usize) {
+        let index = LENGTH_BASES
+            .iter()
+            .rposition(|&base| usize::from(base) <= len)
+            .expect("The match is at least 3 bytes long");
+        self.symbol(257 + index as u16);
+        self.write(
+            (len - usize::from(LENGTH_BASES[index])) as u32,
+            LENGTH_EXTRA[index],
+        );
+
+        let index = DISTANCE_BASES
+            .iter()
+            .rposition(|&base| usize::from(base) <= distance)
+            .expect("The distance is at least 1");
+        self.code(index as u32, 5);
+        self.write(
+            (distance - usize::from(DISTANCE_BASES[index])) as u32,
+            DISTANCE_EXTRA[index],
+        );
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads bits least significant first, like [`BitWriter`] writes them
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+    }
+
+    impl BitReader<'_> {
+        fn bits(&mut self, count: u8) -> u32 {
+            (0..count).fold(0, |value, i| {
+                let bit = (self.bytes[self.pos / 8] >> (self.pos % 8)) & 1;
+                self.pos += 1;
+                value | u32::from(bit) << i
+            })
+        }
+
+        /// Huffman codes start with their most significant bit
+        fn code(&mut self, count: u8) -> u32 {
+            (0..count).fold(0, |code, _| code << 1 | self.bits(1))
+        }
+
+        fn symbol(&mut self) -> usize {
+            let code = self.code(7);
+            if code < 0x18 {
+                return 256 + code as usize;
+            }
+            let code = code << 1 | self.bits(1);
+            match code {
+                0x30..=0xbf => code as usize - 0x30,
+                0xc0..=0xc7 => 280 + code as usize - 0xc0,
+                _ => 144 + (code << 1 | self.bits(1)) as usize - 0x190,
+            }
+        }
+    }
+
+    /// Decompresses a zlib stream consisting of a single block with fixed huffman codes
+    fn inflate(data: &[u8]) -> Vec<u8> {
+        assert_eq!(data[..2], [0x78, 0x01]);
+        let mut bits = BitReader {
+            bytes: &data[2..data.len() - 4],
+            pos: 0,
+        };
+        assert_eq!((bits.bits(1), bits.bits(2)), (1, 1));
+
+        let mut out = Vec::<u8>::new();
+        loop {
+            let symbol = bits.symbol();
+            match symbol {
+                0..=255 => out.push(symbol as u8),
+                256 => break,
+                _ => {
+                    let index = symbol - 257;
+                    let len =
+                        usize::from(LENGTH_BASES[index]) + bits.bits(LENGTH_EXTRA[index]) as usize;
+                    let index = bits.code(5) as usize;
+                    let distance = usize::from(DISTANCE_BASES[index])
+                        + bits.bits(DISTANCE_EXTRA[index]) as usize;
+                    for _ in 0..len {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            }
+        }
+
+        let checksum = u32::from_be_bytes(data[data.len() - 4..].try_into().unwrap());
+        assert_eq!(checksum, adler32(&out));
+        out
+    }
+
+    #[test]
+    fn test_encode() {
+        let (red, white) = ([228, 3, 3], [255, 255, 255]);
+        let image = Image {
+            width: 3,
+            height: 4,
+            pixels: [[red; 3], [red; 3], [white; 3], [red, white, red]].concat(),
+        };
+        let mut png = Vec::new();
+        encode(&mut png, &image).unwrap();
+
+        assert_eq!(png[..8], SIGNATURE);
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(kind.iter().chain(data)));
+            chunks.push((kind, data));
+            rest = &rest[12 + len..];
+        }
+
+        let kinds = chunks.iter().map(|(kind, _)| *kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        // 3x4 pixels, bit depth 8, truecolor, deflate, adaptive filtering, no interlacing
+        assert_eq!(
+            chunks[0].1,
+            [0, 0, 0, 3, 0, 0, 0, 4, 8, 2, 0, 0, 0].as_slice()
+        );
+
+        // Undo the "Up" filter of every row
+        let filtered = inflate(chunks[1].1);
+        let stride = image.width * BYTES_PER_PIXEL;
+        assert_eq!(filtered.len(), (stride + 1) * image.height);
+        let mut previous = vec![0; stride];
+        let mut pixels = Vec::new();
+        for row in filtered.chunks(stride + 1) {
+            assert_eq!(row[0], FILTER_UP);
+            for (byte, above) in row[1..].iter().zip(&mut previous) {
+                *above = byte.wrapping_add(*above);
+            }
+            pixels.extend_from_slice(&previous);
+        }
+        assert_eq!(pixels, image.pixels.as_flattened());
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+}
@@ -0,0 +1,105 @@
+use super::{cells, end_line};
+use crate::{
+    color::{ColorMode, RESET},
+    flag::Flag,
+    font::{self, GLYPH_HEIGHT, GLYPH_WIDTH},
+    overlay::Size,
+};
+use std::{io, num::NonZero};
+
+/// Pixels between two glyphs
+const SPACING: usize = 1;
+/// Drawn instead of the colors if there are none
+const FULL_BLOCK: char = '█';
+
+/// How many columns `text` spans without scaling, every pixel is two columns wide so it's
+/// about square
+pub fn natural_width(text: &str) -> usize {
+    pixel_width(text.chars().count()) * 2
+}
+
+/// How many pixels a line of `glyphs` glyphs spans
+const fn pixel_width(glyphs: usize) -> usize {
+    (glyphs * (GLYPH_WIDTH + SPACING)).saturating_sub(SPACING)
+}
+
+/// Renders `text` in big letters filled with `flag`.
+///
+/// The text is scaled to `width` columns and its height follows unless `height` is given, the
+/// flag is laid out over the whole banner just like [`super::render`] would print it.
+pub fn render(
+    out: &mut impl io::Write,
+    flag: Flag,
+    text: &str,
+    width: NonZero<usize>,
+    height: Option<NonZero<usize>>,
+    slope: NonZero<usize>,
+    mode: ColorMode,
+) -> io::Result<()> {
+    let glyphs = text.chars().map(font::glyph).collect::<Vec<_>>();
+    let pixels = pixel_width(glyphs.len());
+    if pixels == 0 {
+        return Ok(());
+    }
+
+    let width = width.get();
+    let height = height.map_or_else(
+        || {
+            (GLYPH_HEIGHT * width)
+                .div_ceil(pixels * 2)
+                .max(GLYPH_HEIGHT)
+        },
+        NonZero::get,
+    );
+
+    let cells = cells(flag, Size::new(height, width), slope);
+
+    for (y, row) in cells.iter().enumerate() {
+        let glyph_y = y * GLYPH_HEIGHT / height;
+        for (x, cell) in row.iter().enumerate() {
+            let pixel = x * pixels / width;
+            let (index, glyph_x) = (
+                pixel / (GLYPH_WIDTH + SPACING),
+                pixel % (GLYPH_WIDTH + SPACING),
+            );
+            let set = glyph_x < GLYPH_WIDTH && font::is_set(&glyphs[index], glyph_x, glyph_y);
+
+            match (set, mode) {
+                (true, ColorMode::None) => write!(out, "{FULL_BLOCK}")?,
+                (true, _) => cell.write(out, mode)?,
+                (false, ColorMode::None) => write!(out, " ")?,
+                (false, _) => write!(out, "{RESET} ")?,
+            }
+        }
+        end_line(out, mode)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_banner() {
+        let mut out = Vec::new();
+        let width = NonZero::new(natural_width("Hi")).unwrap();
+        render(
+            &mut out,
+            Flag::Trans,
+            "Hi",
+            width,
+            None,
+            NonZero::<usize>::MIN,
+            ColorMode::None,
+        )
+        .unwrap();
+
+        let banner = String::from_utf8(out).unwrap();
+        let lines = banner.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), GLYPH_HEIGHT);
+        assert_eq!(lines[0], "██      ██    ██████  ");
+        assert_eq!(lines[3], "██████████      ██    ");
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Paint, StripeMap, end_line};
+use crate::{
+    color::ColorMode,
+    flag::Flag,
+    overlay::{Shape, Size},
+};
+use std::{io, num::NonZero};
+
+const UPPER_HALF: char = '▀';
+
+/// Renders `flag` using half blocks with separate fore- and background colors, so every cell
+/// shows two pixels on top of each other.
+///
+/// The stripes are distributed over twice as many rows as `size` has, so their borders can fall
+/// in the middle of a cell. Overlays are sampled from their vector shapes, except for charachters
+/// which are printed as they are.
+pub fn render(
+    out: &mut impl io::Write,
+    flag: Flag,
+    size: Size,
+    slope: NonZero<usize>,
+    mode: ColorMode,
+) -> io::Result<()> {
+    let pixel_height = size.height * 2;
+    let stripes = StripeMap::new(flag, Size::new(pixel_height, size.width));
+
+    let overlays = flag.overlays(slope, size);
+    let shapes = overlays
+        .iter()
+        .map(|overlay| (overlay.shape(size), overlay.foreground()))
+        .collect::<Vec<_>>();
+
+    let pixel = |x: usize, y: usize| {
+        let point = (x as f32 + 0.5, (y as f32 + 0.5) / 2.0);
+        shapes
+            .iter()
+            .filter(|(shape, _)| !matches!(shape, Shape::Text { .. }))
+            .find(|(shape, _)| shape.contains(point))
+            .map_or_else(
+                || Paint::Stripe(stripes.at((x, y))),
+                |(_, color)| Paint::Overlay(*color),
+            )
+    };
+
+    for row in 0..size.height {
+        for col in 0..size.width {
+            let top = pixel(col, row * 2);
+            let bottom = pixel(col, row * 2 + 1);
+
+            let char = overlays
+                .iter()
+                .zip(&shapes)
+                .filter(|(_, (shape, _))| matches!(shape, Shape::Text { .. }))
+                .find_map(|(overlay, _)| {
+                    Some((overlay.at_pos(col, row, size)?, overlay.foreground()))
+                });
+
+            match char {
+                Some((char, color)) => {
+                    write!(out, "{}{}{char}", top.bg_in(mode), color.fg_in(mode))?;
+                }
+                None if top.bg_in(mode) == bottom.bg_in(mode) => {
+                    write!(out, "{} ", top.bg_in(mode))?;
+                }
+                None => {
+                    write!(out, "{}{}{UPPER_HALF}", bottom.bg_in(mode), top.fg_in(mode))?;
+                }
+            }
+        }
+
+        end_line(out, mode)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::RESET;
+
+    #[test]
+    fn test_half_block() {
+        let mut buf = Vec::new();
+        render(
+            &mut buf,
+            Flag::Trans,
+            Size::new(2, 2),
+            NonZero::<usize>::MIN,
+            ColorMode::Ansi,
+        )
+        .unwrap();
+
+        // The five stripes are spread over four pixel rows, the first four get one each and the
+        // final blue one none, so the bottom half of the second line is pink
+        let out = String::from_utf8(buf).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            format!("\x1b[45m\x1b[36m{UPPER_HALF}").repeat(2) + RESET
+        );
+        assert_eq!(
+            lines[1],
+            format!("\x1b[45m\x1b[97m{UPPER_HALF}").repeat(2) + RESET
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{
+    color::{Color, ColorMode, RESET},
+    flag::{FallbackedColor, Flag, Layout},
+    overlay::{Coord, Overlay, Size},
+};
+use std::{io, num::NonZero};
+use transform::Transform;
+
+pub mod banner;
+pub mod half_block;
+pub mod raster;
+pub mod svg;
+pub mod transform;
+pub mod wave;
+
+/// Distributes `height` rows over stripes proportionally to their `weights`.
+///
+/// Every stripe gets the whole rows of its share, the rows left over go to the stripes with the
+/// largest remainders, the upper one first if they are equal.
+pub(crate) fn distribute(weights: &[usize], height: usize) -> Vec<usize> {
+    // Widened so the products can't overflow, a share is never larger than `height`
+    let total = weights.iter().map(|&weight| weight as u128).sum::<u128>();
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+    let share = |weight: usize| weight as u128 * height as u128;
+
+    let mut rows = weights
+        .iter()
+        .map(|&weight| (share(weight) / total) as usize)
+        .collect::<Vec<_>>();
+    let mut by_remainder = (0..weights.len()).collect::<Vec<_>>();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse(share(weights[i]) % total));
+
+    let left = height - rows.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(left) {
+        rows[i] += 1;
+    }
+    rows
+}
+
+/// Which stripe covers each cell of a flag.
+///
+/// The flag is split into bands of rows and bands of columns, every combination of a row band and
+/// a column band is covered by one stripe. Horizontal stripes have a single column band, vertical
+/// ones a single row band.
+pub(crate) struct StripeMap {
+    stripes: Vec<FallbackedColor>,
+    layout: Layout,
+    /// How many rows each row band spans
+    row_bands: Vec<usize>,
+    /// How many columns each column band spans
+    column_bands: Vec<usize>,
+    /// The row band of every row
+    rows: Vec<usize>,
+    /// The column band of every column
+    columns: Vec<usize>,
+}
+
+impl StripeMap {
+    pub fn new(flag: Flag, size: Size) -> Self {
+        let stripes = flag.stripes();
+        let layout = flag.layout();
+        let (row_bands, column_bands) = match layout {
+            Layout::Horizontal => (distribute(&flag.weights(), size.height), vec![size.width]),
+            Layout::Vertical => (vec![size.height], distribute(&flag.weights(), size.width)),
+            Layout::Grid { columns } => {
+                let rows = stripes.len().div_ceil(columns.get());
+                (
+                    distribute(&vec![1; rows], size.height),
+                    distribute(&vec![1; columns.get()], size.width),
+                )
+            }
+        };
+
+        let band_of = |bands: &[usize]| {
+            bands
+                .iter()
+                .enumerate()
+                .flat_map(|(band, &len)| std::iter::repeat_n(band, len))
+                .collect()
+        };
+
+        Self {
+            rows: band_of(&row_bands),
+            columns: band_of(&column_bands),
+            stripes,
+            layout,
+            row_bands,
+            column_bands,
+        }
+    }
+
+    fn stripe(&self, row_band: usize, column_band: usize) -> FallbackedColor {
+        let index = match self.layout {
+            Layout::Horizontal => row_band,
+            Layout::Vertical => column_band,
+            Layout::Grid { columns } => row_band * columns.get() + column_band,
+        };
+        self.stripes[index]
+    }
+
+    /// The stripe at the cell `(x, y)`, coordinates outside of the flag are clamped to its edges
+    pub fn at(&self, (x, y): Coord) -> FallbackedColor {
+        let band = |bands: &[usize], i: usize| bands.get(i).or(bands.last()).copied();
+        let row_band = band(&self.rows, y).unwrap_or(0);
+        let column_band = band(&self.columns, x).unwrap_or(0);
+        self.stripe(row_band, column_band)
+    }
+
+    /// The rectangles covered by a single stripe as `(x, y, width, height)` in cells
+    pub fn rects(&self) -> Vec<((usize, usize, usize, usize), FallbackedColor)> {
+        let offsets = |bands: &[usize]| {
+            bands
+                .iter()
+                .scan(0, |offset, &len| {
+                    let start = *offset;
+                    *offset += len;
+                    Some((start, len))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let columns = offsets(&self.column_bands);
+        offsets(&self.row_bands)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row_band, (y, height))| {
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(move |(column_band, &(x, width))| {
+                        ((x, y, width, height), (row_band, column_band))
+                    })
+            })
+            .filter(|((_, _, width, height), _)| *width > 0 && *height > 0)
+            .map(|(rect, (row_band, column_band))| (rect, self.stripe(row_band, column_band)))
+            .collect()
+    }
+}
+
+/// A color of the flag, either a stripe with its fallback or the color of an overlay
+#[derive(Clone, Copy)]
+pub enum Paint {
+    Stripe(FallbackedColor),
+    Overlay(Color),
+}
+
+impl Paint {
+    pub fn fg_in(self, mode: ColorMode) -> String {
+        match self {
+            Self::Stripe(color) => color.fg_in(mode),
+            Self::Overlay(color) => color.fg_in(mode),
+        }
+    }
+
+    pub fn bg_in(self, mode: ColorMode) -> String {
+        match self {
+            Self::Stripe(color) => color.bg_in(mode),
+            Self::Overlay(color) => color.bg_in(mode),
+        }
+    }
+
+    pub const fn true_color(self) -> Color {
+        match self {
+            Self::Stripe(color) => color.true_color(),
+            Self::Overlay(color) => color,
+        }
+    }
+}
+
+/// A single charachter of a flag
+#[derive(Clone, Copy)]
+pub struct Cell {
+    pub char: char,
+    pub fg: Paint,
+    pub bg: Paint,
+}
+
+impl Cell {
+    /// Writes the cell, spaces only set the background
+    pub fn write(&self, out: &mut impl io::Write, mode: ColorMode) -> io::Result<()> {
+        if self.char == ' ' {
+            write!(out, "{} ", self.bg.bg_in(mode))
+        } else {
+            write!(
+                out,
+                "{}{}{}",
+                self.bg.bg_in(mode),
+                self.fg.fg_in(mode),
+                self.char
+            )
+        }
+    }
+}
+
+/// Lays out `flag` as rows of charachters, the rows (or columns) are distributed over the stripes
+/// according to their weights.
+pub fn cells(flag: Flag, size: Size, slope: NonZero<usize>) -> Vec<Vec<Cell>> {
+    let stripes = StripeMap::new(flag, size);
+    let overlays = flag.overlays(slope, size);
+
+    (0..size.height)
+        .map(|y| {
+            (0..size.width)
+                .map(|x| {
+                    let stripe = Paint::Stripe(stripes.at((x, y)));
+                    cell(stripe, &overlays, (x, y), size)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The cell of the flag at `(x, y)` with `stripe` as background
+fn cell(
+    stripe: Paint,
+    overlays: &[Box<dyn Overlay<Foreground = Color>>],
+    (x, y): Coord,
+    size: Size,
+) -> Cell {
+    let Some((index, overlay, char)) = overlays.iter().enumerate().find_map(|(i, ov)| {
+        let char = ov.at_pos(x, y, size)?;
+        Some((i, ov, char))
+    }) else {
+        return Cell {
+            char: ' ',
+            fg: stripe,
+            bg: stripe,
+        };
+    };
+
+    let bg = overlays
+        .iter()
+        .skip(index + 1)
+        .find(|overlay| overlay.at_pos(x, y, size).is_some())
+        .map_or(stripe, |overlay| Paint::Overlay(overlay.foreground()));
+
+    Cell {
+        char,
+        fg: Paint::Overlay(overlay.foreground()),
+        bg,
+    }
+}
+
+/// Renders a full frame of `flag` into `out` using ANSI escape codes, see [`cells`]. The cells
+/// are turned and flipped by `transform` to fill `size`.
+pub fn render(
+    out: &mut impl io::Write,
+    flag: Flag,
+    size: Size,
+    slope: NonZero<usize>,
+    mode: ColorMode,
+    transform: Transform,
+) -> io::Result<()> {
+    let cells = cells(flag, transform.source_size(size), slope);
+    for row in transform.cells(&cells, size) {
+        for cell in row {
+            cell.write(out, mode)?;
+        }
+        end_line(out, mode)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn end_line(out: &mut impl io::Write, mode: ColorMode) -> io::Result<()> {
+    if mode == ColorMode::None {
+        writeln!(out)
+    } else {
+        writeln!(out, "{RESET}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flag::custom::CustomFlag;
+
+    #[test]
+    fn test_render_to_buffer() {
+        let mut buf = Vec::new();
+        render(
+            &mut buf,
+            Flag::Trans,
+            Size::new(7, 4),
+            NonZero::<usize>::MIN,
+            ColorMode::Ansi,
+            Transform::default(),
+        )
+        .unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "\x1b[46m ".repeat(4) + RESET);
+        assert_eq!(lines[4], "\x1b[107m ".repeat(4) + RESET);
+    }
+
+    #[test]
+    fn test_stripe_map() {
+        let flag = CustomFlag::from_toml(
+            r##"
+            name = "Grid"
+            layout = { type = "grid", columns = 2 }
+            stripes = [
+                { color = "#ff0000" },
+                { color = "#00ff00" },
+                { color = "#0000ff" },
+                { color = "#ffffff" },
+            ]
+            "##,
+        )
+        .unwrap();
+        let flag = Flag::Custom(Box::leak(Box::new(flag)));
+
+        let stripes = StripeMap::new(flag, Size::new(4, 6));
+        let hex = |coord| stripes.at(coord).true_color().hex();
+        assert_eq!(hex((0, 0)), "#ff0000");
+        assert_eq!(hex((3, 1)), "#00ff00");
+        assert_eq!(hex((2, 2)), "#0000ff");
+        assert_eq!(hex((5, 3)), "#ffffff");
+        // Cells past the edges belong to the nearest field
+        assert_eq!(hex((9, 9)), "#ffffff");
+        assert_eq!(stripes.rects().len(), 4);
+    }
+
+    #[test]
+    fn test_distribute() {
+        assert_eq!(distribute(&[1, 1, 1, 1, 1], 7), [2, 2, 1, 1, 1]);
+        assert_eq!(distribute(&[2, 1, 2], 15), [6, 3, 6]);
+        assert_eq!(distribute(&[2, 1, 2], 4), [2, 1, 1]);
+        assert_eq!(distribute(&[1, 1, 1], 0), [0, 0, 0]);
+        assert_eq!(distribute(&[usize::MAX, usize::MAX], 3), [2, 1]);
+    }
+}
@@ -0,0 +1,84 @@
+use super::StripeMap;
+use crate::{
+    flag::Flag,
+    overlay::{Point, Size},
+};
+use std::num::NonZero;
+
+/// Samples per pixel along each axis, used for anti aliasing
+const SUPERSAMPLING: usize = 2;
+
+/// An RGB image
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub fn rows(&self) -> impl Iterator<Item = &[[u8; 3]]> {
+        self.pixels.chunks(self.width)
+    }
+}
+
+/// Rasterizes `flag` into an image of `width` x `height` pixels.
+///
+/// The stripes and overlays are laid out for a terminal of `size` charachter cells which is then
+/// stretched over the image, the overlays are sampled from their vector shapes rather than from
+/// their charachters.
+pub fn rasterize(
+    flag: Flag,
+    size: Size,
+    slope: NonZero<usize>,
+    (width, height): (NonZero<usize>, NonZero<usize>),
+) -> Image {
+    let (width, height) = (width.get(), height.get());
+    let stripes = StripeMap::new(flag, size);
+    let shapes = flag
+        .overlays(slope, size)
+        .into_iter()
+        .map(|overlay| (overlay.shape(size), overlay.foreground().rgb()))
+        .collect::<Vec<_>>();
+
+    let sample = |point: Point| {
+        shapes
+            .iter()
+            .find(|(shape, _)| shape.contains(point))
+            .map(|(_, color)| *color)
+            .unwrap_or_else(|| {
+                let cell = (point.0 as usize, point.1 as usize);
+                stripes.at(cell).true_color().rgb()
+            })
+    };
+
+    let scale = (
+        size.width as f32 / width as f32,
+        size.height as f32 / height as f32,
+    );
+    let step = 1.0 / SUPERSAMPLING as f32;
+
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let mut sum = [0_usize; 3];
+            for sy in 0..SUPERSAMPLING {
+                for sx in 0..SUPERSAMPLING {
+                    let point = (
+                        (x as f32 + (sx as f32 + 0.5) * step) * scale.0,
+                        (y as f32 + (sy as f32 + 0.5) * step) * scale.1,
+                    );
+                    for (sum, channel) in sum.iter_mut().zip(sample(point)) {
+                        *sum += usize::from(channel);
+                    }
+                }
+            }
+            sum.map(|channel| (channel / (SUPERSAMPLING * SUPERSAMPLING)) as u8)
+        })
+        .collect();
+
+    Image {
+        width,
+        height,
+        pixels,
+    }
+}
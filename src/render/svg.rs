@@ -0,0 +1,162 @@
+use super::{StripeMap, transform::Transform};
+use crate::{
+    flag::Flag,
+    overlay::{Point, Shape, Size},
+};
+use std::{io, num::NonZero};
+
+/// The width of a single charachter cell in SVG user units
+const CELL_WIDTH: f32 = 10.0;
+/// The height of a single charachter cell in SVG user units
+const CELL_HEIGHT: f32 = 20.0;
+
+/// Renders `flag` as a SVG document into `out`, one charachter cell is [`CELL_WIDTH`] x
+/// [`CELL_HEIGHT`] units large. The whole image is turned and flipped by `transform`.
+pub fn render(
+    out: &mut impl io::Write,
+    flag: Flag,
+    size: Size,
+    slope: NonZero<usize>,
+    transform: Transform,
+) -> io::Result<()> {
+    let stripes = StripeMap::new(flag, size);
+    let overlays = flag.overlays(slope, size);
+
+    let width = size.width as f32 * CELL_WIDTH;
+    let height = size.height as f32 * CELL_HEIGHT;
+    let (transform, (width, height)) = transform.svg(width, height);
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )?;
+    if !transform.is_empty() {
+        writeln!(out, r#"<g transform="{transform}">"#)?;
+    }
+
+    for ((x, y, rect_width, rect_height), stripe) in stripes.rects() {
+        writeln!(
+            out,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            x as f32 * CELL_WIDTH,
+            y as f32 * CELL_HEIGHT,
+            rect_width as f32 * CELL_WIDTH,
+            rect_height as f32 * CELL_HEIGHT,
+            stripe.true_color().hex(),
+        )?;
+    }
+
+    // The first overlay is the topmost one, so it has to be drawn last
+    for overlay in overlays.iter().rev() {
+        let fill = overlay.foreground().hex();
+        match overlay.shape(size) {
+            Shape::Polygon(points) => {
+                let points = points
+                    .into_iter()
+                    .map(|point| {
+                        let (x, y) = scale(point);
+                        format!("{x},{y}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(out, r#"  <polygon points="{points}" fill="{fill}"/>"#)?;
+            }
+            Shape::Ring {
+                center,
+                outer,
+                inner,
+            } => {
+                let path = format!(
+                    "{} {}",
+                    ellipse_path(center, outer),
+                    ellipse_path(center, inner)
+                );
+                writeln!(
+                    out,
+                    r#"  <path d="{path}" fill="{fill}" fill-rule="evenodd"/>"#
+                )?;
+            }
+            Shape::Text { center, char } => {
+                let (x, y) = scale(center);
+                writeln!(
+                    out,
+                    r#"  <text x="{x}" y="{y}" font-size="{CELL_HEIGHT}" font-family="monospace" text-anchor="middle" dominant-baseline="central" fill="{fill}">{}</text>"#,
+                    escape(char),
+                )?;
+            }
+        }
+    }
+
+    if !transform.is_empty() {
+        writeln!(out, "</g>")?;
+    }
+    writeln!(out, "</svg>")
+}
+
+fn scale((x, y): Point) -> Point {
+    (x * CELL_WIDTH, y * CELL_HEIGHT)
+}
+
+/// A closed path of an ellipse built out of two arcs
+fn ellipse_path(center: Point, (rx, ry): (f32, f32)) -> String {
+    let (cx, cy) = scale(center);
+    let (rx, ry) = (rx * CELL_WIDTH, ry * CELL_HEIGHT);
+    format!(
+        "M {} {cy} a {rx} {ry} 0 1 0 {} 0 a {rx} {ry} 0 1 0 {} 0 Z",
+        cx - rx,
+        rx * 2.0,
+        -rx * 2.0,
+    )
+}
+
+fn escape(char: char) -> String {
+    match char {
+        '<' => String::from("&lt;"),
+        '>' => String::from("&gt;"),
+        '&' => String::from("&amp;"),
+        ch => String::from(ch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg() {
+        let mut buf = Vec::new();
+        render(
+            &mut buf,
+            Flag::Demisexual,
+            Size::new(8, 10),
+            NonZero::<usize>::MIN,
+            Transform::default(),
+        )
+        .unwrap();
+
+        let out = String::from_utf8(buf).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="160" viewBox="0 0 100 160">"#
+        );
+        assert_eq!(
+            lines[1],
+            r##"  <rect x="0" y="0" width="100" height="40" fill="#ffffff"/>"##
+        );
+        assert_eq!(
+            lines[3],
+            r##"  <rect x="0" y="80" width="100" height="40" fill="#800080"/>"##
+        );
+        assert_eq!(
+            lines[5],
+            r##"  <rect x="0" y="140" width="100" height="20" fill="#d2d2d2"/>"##
+        );
+        // The triangle reaches half a cell into the flag at the top and bottom
+        assert_eq!(
+            lines[6],
+            r##"  <polygon points="0,0 5,0 45,80 5,160 0,160" fill="#000000"/>"##
+        );
+        assert_eq!(lines[7], "</svg>");
+    }
+}
@@ -0,0 +1,248 @@
+use super::{Cell, raster::Image};
+use crate::overlay::{CELL_ASPECT, Coord, Size};
+use clap::ValueEnum;
+
+/// The block charachters made of the quadrants of a cell, indexed by a bitmask of the filled
+/// quadrants: 1 is the upper left, 2 the upper right, 4 the lower left and 8 the lower right one
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+const UPPER_LEFT: u8 = 1;
+const UPPER_RIGHT: u8 = 2;
+const LOWER_LEFT: u8 = 4;
+const LOWER_RIGHT: u8 = 8;
+
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rotation {
+    #[default]
+    #[value(name = "0")]
+    None,
+    /// A quarter turn clockwise
+    #[value(name = "90")]
+    Clockwise,
+    #[value(name = "180")]
+    Half,
+    /// A quarter turn counterclockwise
+    #[value(name = "270")]
+    Counterclockwise,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum Mirror {
+    /// Flip left and right
+    Horizontal,
+    /// Flip top and bottom
+    Vertical,
+}
+
+/// A rotation followed by an optional mirroring
+#[derive(Clone, Copy, Default)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub mirror: Option<Mirror>,
+}
+
+impl Transform {
+    pub const fn is_identity(self) -> bool {
+        matches!(self.rotation, Rotation::None) && self.mirror.is_none()
+    }
+
+    /// Whether width and height are swapped
+    pub const fn is_quarter_turn(self) -> bool {
+        matches!(
+            self.rotation,
+            Rotation::Clockwise | Rotation::Counterclockwise
+        )
+    }
+
+    /// The size the flag has to be laid out in to fill `size` cells once it is transformed
+    pub const fn source_size(self, size: Size) -> Size {
+        if self.is_quarter_turn() {
+            Size::new(size.width, size.height)
+        } else {
+            size
+        }
+    }
+
+    /// The cell of the untransformed flag that ends up at `(x, y)` of the output of `size`
+    const fn source(self, (x, y): Coord, size: Size) -> Coord {
+        let (x, y) = match self.mirror {
+            Some(Mirror::Horizontal) => (size.width - 1 - x, y),
+            Some(Mirror::Vertical) => (x, size.height - 1 - y),
+            None => (x, y),
+        };
+        match self.rotation {
+            Rotation::None => (x, y),
+            Rotation::Clockwise => (y, size.width - 1 - x),
+            Rotation::Half => (size.width - 1 - x, size.height - 1 - y),
+            Rotation::Counterclockwise => (size.height - 1 - y, x),
+        }
+    }
+
+    /// Turns and flips block charachters just like the cells, other charachters stay as they are
+    pub fn glyph(self, char: char) -> char {
+        let Some(mut mask) = QUADRANTS.iter().position(|&c| c == char).map(|i| i as u8) else {
+            return char;
+        };
+
+        let quadrant = |mask: u8, from: u8, to: u8| if mask & from == 0 { 0 } else { to };
+        let turns = match self.rotation {
+            Rotation::None => 0,
+            Rotation::Clockwise => 1,
+            Rotation::Half => 2,
+            Rotation::Counterclockwise => 3,
+        };
+        for _ in 0..turns {
+            mask = quadrant(mask, UPPER_LEFT, UPPER_RIGHT)
+                | quadrant(mask, UPPER_RIGHT, LOWER_RIGHT)
+                | quadrant(mask, LOWER_RIGHT, LOWER_LEFT)
+                | quadrant(mask, LOWER_LEFT, UPPER_LEFT);
+        }
+        mask = match self.mirror {
+            Some(Mirror::Horizontal) => {
+                quadrant(mask, UPPER_LEFT, UPPER_RIGHT)
+                    | quadrant(mask, UPPER_RIGHT, UPPER_LEFT)
+                    | quadrant(mask, LOWER_LEFT, LOWER_RIGHT)
+                    | quadrant(mask, LOWER_RIGHT, LOWER_LEFT)
+            }
+            Some(Mirror::Vertical) => {
+                quadrant(mask, UPPER_LEFT, LOWER_LEFT)
+                    | quadrant(mask, LOWER_LEFT, UPPER_LEFT)
+                    | quadrant(mask, UPPER_RIGHT, LOWER_RIGHT)
+                    | quadrant(mask, LOWER_RIGHT, UPPER_RIGHT)
+            }
+            None => mask,
+        };
+
+        QUADRANTS[usize::from(mask)]
+    }
+
+    /// Transforms the cells of a flag laid out in [`Transform::source_size`] into `size` cells.
+    ///
+    /// The cells are moved one by one, so turning a flag by a quarter doesn't keep its
+    /// proportions since cells are higher than wide.
+    pub fn cells(self, cells: &[Vec<Cell>], size: Size) -> Vec<Vec<Cell>> {
+        (0..size.height)
+            .map(|y| {
+                (0..size.width)
+                    .filter_map(|x| {
+                        let (x, y) = self.source((x, y), size);
+                        let cell = cells.get(y)?.get(x)?;
+                        Some(Cell {
+                            char: self.glyph(cell.char),
+                            ..*cell
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Transforms a whole image, unlike the cells it keeps its proportions
+    pub fn image(self, image: Image) -> Image {
+        let (width, height) = if self.is_quarter_turn() {
+            (image.height, image.width)
+        } else {
+            (image.width, image.height)
+        };
+        let size = Size::new(height, width);
+
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|coord| {
+                let (x, y) = self.source(coord, size);
+                image.pixels[y * image.width + x]
+            })
+            .collect();
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// How many cells an image of a flag which was `size` cells large takes up once it is
+    /// transformed
+    pub fn image_cells(self, size: Size) -> Size {
+        if self.is_quarter_turn() {
+            Size::new(
+                (size.width as f32 / CELL_ASPECT).round().max(1.0) as usize,
+                (size.height as f32 * CELL_ASPECT) as usize,
+            )
+        } else {
+            size
+        }
+    }
+
+    /// A SVG transform list turning an image of `width` x `height` units, together with the size
+    /// of the transformed image
+    pub fn svg(self, width: f32, height: f32) -> (String, (f32, f32)) {
+        let (rotate, size) = match self.rotation {
+            Rotation::None => (String::new(), (width, height)),
+            Rotation::Clockwise => (format!("translate({height} 0) rotate(90)"), (height, width)),
+            Rotation::Half => (
+                format!("translate({width} {height}) rotate(180)"),
+                (width, height),
+            ),
+            Rotation::Counterclockwise => {
+                (format!("translate(0 {width}) rotate(270)"), (height, width))
+            }
+        };
+        let mirror = match self.mirror {
+            Some(Mirror::Horizontal) => format!("translate({} 0) scale(-1 1)", size.0),
+            Some(Mirror::Vertical) => format!("translate(0 {}) scale(1 -1)", size.1),
+            None => String::new(),
+        };
+
+        // The transform on the left is applied last
+        let list = [mirror, rotate]
+            .into_iter()
+            .filter(|transform| !transform.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        (list, size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph() {
+        let rotate = |rotation| Transform {
+            rotation,
+            mirror: None,
+        };
+        let mirror = |mirror| Transform {
+            rotation: Rotation::None,
+            mirror: Some(mirror),
+        };
+
+        assert_eq!(mirror(Mirror::Horizontal).glyph('▌'), '▐');
+        assert_eq!(mirror(Mirror::Horizontal).glyph('▙'), '▟');
+        assert_eq!(mirror(Mirror::Vertical).glyph('▙'), '▛');
+        assert_eq!(rotate(Rotation::Clockwise).glyph('▌'), '▀');
+        assert_eq!(rotate(Rotation::Clockwise).glyph('▙'), '▛');
+        assert_eq!(rotate(Rotation::Half).glyph('▞'), '▞');
+        assert_eq!(rotate(Rotation::Counterclockwise).glyph('▌'), '▄');
+        assert_eq!(rotate(Rotation::Clockwise).glyph('π'), 'π');
+    }
+
+    #[test]
+    fn test_image() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![[1; 3], [2; 3]],
+        };
+        let transform = Transform {
+            rotation: Rotation::Clockwise,
+            mirror: Some(Mirror::Vertical),
+        };
+        let image = transform.image(image);
+        assert_eq!((image.width, image.height), (1, 2));
+        assert_eq!(image.pixels, [[2; 3], [1; 3]]);
+    }
+}
@@ -0,0 +1,150 @@
+use super::{cells, end_line};
+use crate::{
+    color::{ColorMode, RESET},
+    flag::Flag,
+    overlay::Size,
+    screen::{HIDE_CURSOR, SHOW_CURSOR},
+};
+use std::{
+    f32::consts::TAU,
+    io::{self, Write as _},
+    num::NonZero,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How many columns a full wave spans
+const WAVELENGTH: f32 = 24.0;
+/// How many waves pass a column each second
+const FREQUENCY: f32 = 0.75;
+
+/// How many rows the columns move up and down
+const fn amplitude(height: usize) -> usize {
+    if height >= 20 { height / 10 } else { 1 }
+}
+
+/// Renders a single frame of a waving flag at `time` seconds.
+///
+/// Every column is moved vertically by a sine wave together with the overlays on it, so the frame
+/// is `2 * amplitude` rows higher than the flag.
+pub fn render_frame(
+    out: &mut impl io::Write,
+    flag: Flag,
+    size: Size,
+    slope: NonZero<usize>,
+    mode: ColorMode,
+    time: f32,
+) -> io::Result<()> {
+    let cells = cells(flag, size, slope);
+    let height = cells.len();
+
+    let amplitude = amplitude(height);
+    let offsets = (0..size.width)
+        .map(|x| {
+            let phase = TAU * (x as f32 / WAVELENGTH - time * FREQUENCY);
+            (amplitude as f32 * (1.0 + phase.sin())).round() as usize
+        })
+        .collect::<Vec<_>>();
+
+    for row in 0..height + amplitude * 2 {
+        for (x, &offset) in offsets.iter().enumerate() {
+            let cell = row
+                .checked_sub(offset)
+                .and_then(|y| cells.get(y))
+                .and_then(|row| row.get(x));
+            match cell {
+                Some(cell) => cell.write(out, mode)?,
+                None if mode == ColorMode::None => write!(out, " ")?,
+                None => write!(out, "{RESET} ")?,
+            }
+        }
+        end_line(out, mode)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+pub struct Timing {
+    pub fps: NonZero<u32>,
+    /// Animate until stopped if there is no duration
+    pub duration: Option<Duration>,
+}
+
+/// Draws frames of a waving flag in place until `stop` is set or the duration has passed.
+pub fn animate(
+    out: &mut impl io::Write,
+    flag: Flag,
+    size: Size,
+    slope: NonZero<usize>,
+    mode: ColorMode,
+    Timing { fps, duration }: Timing,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let frame_time = Duration::from_secs(1) / fps.get();
+    let rows = size.height + amplitude(size.height) * 2;
+    let start = Instant::now();
+
+    write!(out, "{HIDE_CURSOR}")?;
+    let mut frame = Vec::new();
+    let mut drawn = false;
+    let result = loop {
+        let now = Instant::now();
+        let elapsed = now - start;
+        if stop.load(Ordering::Relaxed) || duration.is_some_and(|duration| elapsed >= duration) {
+            break Ok(());
+        }
+
+        frame.clear();
+        if drawn {
+            // Move back to the top of the previous frame
+            write!(frame, "\x1b[{rows}A")?;
+        }
+        render_frame(&mut frame, flag, size, slope, mode, elapsed.as_secs_f32())?;
+        if let Err(err) = out.write_all(&frame).and_then(|()| out.flush()) {
+            break Err(err);
+        }
+        drawn = true;
+
+        thread::sleep(frame_time.saturating_sub(now.elapsed()));
+    };
+
+    write!(out, "{SHOW_CURSOR}")?;
+    out.flush()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_animate() {
+        let mut buf = Vec::new();
+        let timing = Timing {
+            fps: NonZero::new(1000).unwrap(),
+            duration: Some(Duration::from_millis(20)),
+        };
+        let size = Size::new(5, 10);
+        let stop = AtomicBool::new(false);
+        animate(
+            &mut buf,
+            Flag::Trans,
+            size,
+            NonZero::<usize>::MIN,
+            ColorMode::Ansi,
+            timing,
+            &stop,
+        )
+        .unwrap();
+
+        // Only the frames after the first one move the cursor back up
+        let out = String::from_utf8(buf).unwrap();
+        let up = format!("\x1b[{}A", size.height + 2);
+        let frames = out.strip_prefix(HIDE_CURSOR).unwrap();
+        assert!(!frames.starts_with(&up));
+        assert!(frames.matches(&up).count() >= 1);
+        assert!(out.ends_with(SHOW_CURSOR));
+    }
+}
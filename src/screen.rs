@@ -0,0 +1,96 @@
+//! Full screen terminal handling for the interactive modes.
+
+use std::{
+    io::{self, Read, Write},
+    mem::MaybeUninit,
+};
+
+const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?1049l";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+pub const CLEAR: &str = "\x1b[2J";
+pub const HOME: &str = "\x1b[H";
+
+pub enum Key {
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Escape,
+}
+
+/// The alternate screen with the terminal in raw mode, so keys can be read as soon as they are
+/// pressed.
+///
+/// Everything is restored once it's dropped.
+pub struct Screen {
+    original: libc::termios,
+}
+
+impl Screen {
+    pub fn enter() -> io::Result<Self> {
+        let original = unsafe {
+            let mut termios = MaybeUninit::uninit();
+            if libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            termios.assume_init()
+        };
+
+        let mut raw = original;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+        raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+        // Reading returns immediately, even if no key was pressed
+        raw.c_cc[libc::VMIN] = 0;
+        raw.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut stdout = io::stdout();
+        write!(stdout, "{ENTER_ALTERNATE_SCREEN}{HIDE_CURSOR}{CLEAR}")?;
+        stdout.flush()?;
+
+        Ok(Self { original })
+    }
+
+    /// All keys pressed since the last call
+    pub fn keys(&mut self) -> io::Result<Vec<Key>> {
+        let mut buf = [0; 64];
+        let len = io::stdin().lock().read(&mut buf)?;
+
+        let mut keys = Vec::new();
+        let mut bytes = buf[..len].iter().copied().peekable();
+        while let Some(byte) = bytes.next() {
+            let key = match byte {
+                0x1b if bytes.next_if_eq(&b'[').is_some() => match bytes.next() {
+                    Some(b'A') => Key::Up,
+                    Some(b'B') => Key::Down,
+                    Some(b'C') => Key::Right,
+                    Some(b'D') => Key::Left,
+                    _ => continue,
+                },
+                0x1b => Key::Escape,
+                // Ctrl-C
+                0x03 => Key::Char('q'),
+                byte => Key::Char(char::from(byte)),
+            };
+            keys.push(key);
+        }
+
+        Ok(keys)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "{SHOW_CURSOR}{LEAVE_ALTERNATE_SCREEN}");
+        let _ = stdout.flush();
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.original);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! An encoder for the Sixel graphics protocol supported by terminals like foot, WezTerm, mlterm
+//! and xterm.
+
+use crate::render::raster::Image;
+use std::{collections::HashMap, io};
+
+/// The maximum number of colors most terminals support in a single image
+const MAX_COLORS: usize = 256;
+
+/// Writes `image` as a sixel sequence.
+///
+/// Sixels use a palette, so only the most common colors are kept and every other pixel, like the
+/// anti aliased edges of overlays, uses the closest of them.
+pub fn encode(out: &mut impl io::Write, image: &Image) -> io::Result<()> {
+    let palette = palette(image);
+    let lookup = palette
+        .iter()
+        .enumerate()
+        .map(|(i, &color)| (color, i))
+        .collect::<HashMap<_, _>>();
+    let indices = image
+        .pixels
+        .iter()
+        .map(|pixel| {
+            lookup.get(pixel).copied().unwrap_or_else(|| {
+                palette
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, color)| distance(color, pixel))
+                    .map_or(0, |(i, _)| i)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // Pixel aspect ratio 1:1, keep the background and the size of the image
+    write!(out, "\x1bP0;1;0q\"1;1;{};{}", image.width, image.height)?;
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        let percent = |channel: &u8| u32::from(*channel) * 100 / 255;
+        write!(out, "#{i};2;{};{};{}", percent(r), percent(g), percent(b))?;
+    }
+
+    for band in indices.chunks(image.width * 6) {
+        let rows = band.len() / image.width;
+        let mut used = band.to_vec();
+        used.sort_unstable();
+        used.dedup();
+
+        for (n, &color) in used.iter().enumerate() {
+            if n > 0 {
+                // Carriage return, the next color is drawn over the same band
+                write!(out, "$")?;
+            }
+            write!(out, "#{color}")?;
+
+            let sixels = (0..image.width).map(|x| {
+                let bits = (0..rows)
+                    .filter(|y| band[y * image.width + x] == color)
+                    .fold(0, |bits, y| bits | (1 << y));
+                char::from(0x3f + bits)
+            });
+            write_runs(out, sixels)?;
+        }
+        write!(out, "-")?;
+    }
+
+    write!(out, "\x1b\\")
+}
+
+/// Writes the sixels with repetitions compressed
+fn write_runs(out: &mut impl io::Write, sixels: impl Iterator<Item = char>) -> io::Result<()> {
+    let mut sixels = sixels.peekable();
+    while let Some(sixel) = sixels.next() {
+        let mut count = 1;
+        while sixels.next_if_eq(&sixel).is_some() {
+            count += 1;
+        }
+
+        if count > 3 {
+            write!(out, "!{count}{sixel}")?;
+        } else {
+            for _ in 0..count {
+                write!(out, "{sixel}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The most common colors of the image
+fn palette(image: &Image) -> Vec<[u8; 3]> {
+    let mut counts = HashMap::<[u8; 3], usize>::new();
+    for &pixel in &image.pixels {
+        *counts.entry(pixel).or_default() += 1;
+    }
+
+    let mut colors = counts.into_iter().collect::<Vec<_>>();
+    colors.sort_unstable_by(|(a_color, a), (b_color, b)| b.cmp(a).then(a_color.cmp(b_color)));
+    colors
+        .into_iter()
+        .take(MAX_COLORS)
+        .map(|(color, _)| color)
+        .collect()
+}
+
+fn distance(a: &[u8; 3], b: &[u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| u32::from(a.abs_diff(*b)).pow(2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let image = Image {
+            width: 5,
+            height: 2,
+            pixels: [[255, 0, 0]; 5].into_iter().chain([[0; 3]; 5]).collect(),
+        };
+
+        let mut buf = Vec::new();
+        encode(&mut buf, &image).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "\x1bP0;1;0q\"1;1;5;2#0;2;0;0;0#1;2;100;0;0#0!5A$#1!5@-\x1b\\"
+        );
+    }
+}
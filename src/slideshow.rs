@@ -0,0 +1,201 @@
+use crate::{
+    color::{ColorMode, RESET},
+    flag::Flag,
+    overlay::Size,
+    render::{Cell, Paint, cells},
+    screen::{HOME, Key, Screen},
+};
+use clap::ValueEnum;
+use std::{
+    io::{self, Write},
+    num::NonZero,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+const TRANSITION_TIME: Duration = Duration::from_millis(600);
+const FRAME_TIME: Duration = Duration::from_millis(30);
+/// Erases the rest of the line
+const CLEAR_LINE: &str = "\x1b[K";
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Transition {
+    /// Switch to the next flag at once
+    None,
+    /// The next flag is pushed in from the left
+    Wipe,
+    /// The colors blend into the next flag, falls back to wipe for less than 256 colors
+    Fade,
+}
+
+#[derive(Clone, Copy)]
+pub struct Options {
+    pub size: Size,
+    pub slope: NonZero<usize>,
+    pub mode: ColorMode,
+    /// How long each flag is shown
+    pub interval: Duration,
+    pub transition: Transition,
+}
+
+/// Shows one flag after another in the alternate screen until `q` is pressed or `stop` is set.
+///
+/// The arrow keys switch to the next or previous flag and space pauses the slideshow.
+pub fn run(flags: &[Flag], options: Options, stop: &AtomicBool) -> io::Result<()> {
+    let Options {
+        size,
+        slope,
+        mode,
+        interval,
+        transition,
+    } = options;
+    let transition = match (transition, mode) {
+        (Transition::Fade, ColorMode::Ansi | ColorMode::None) => Transition::Wipe,
+        (transition, _) => transition,
+    };
+
+    let slides = flags
+        .iter()
+        .map(|&flag| cells(flag, size, slope))
+        .collect::<Vec<_>>();
+    if slides.is_empty() {
+        return Ok(());
+    }
+
+    let mut screen = Screen::enter()?;
+    let mut out = io::stdout().lock();
+
+    let mut current = 0;
+    let mut previous = None;
+    let mut shown_at = Instant::now();
+    let mut paused = false;
+    let mut dirty = true;
+
+    while !stop.load(Ordering::Relaxed) {
+        let mut next = None;
+        for key in screen.keys()? {
+            match key {
+                Key::Char('q') | Key::Escape => return Ok(()),
+                Key::Char('n' | 'l') | Key::Right | Key::Down => {
+                    next = Some((current + 1) % slides.len());
+                }
+                Key::Char('p' | 'h') | Key::Left | Key::Up => {
+                    next = Some((current + slides.len() - 1) % slides.len());
+                }
+                Key::Char(' ') => {
+                    paused = !paused;
+                    previous = None;
+                    shown_at = Instant::now();
+                    dirty = true;
+                }
+                Key::Char(_) => {}
+            }
+        }
+
+        if next.is_none() && !paused && shown_at.elapsed() >= interval {
+            next = Some((current + 1) % slides.len());
+        }
+        if let Some(next) = next {
+            previous = Some(current);
+            current = next;
+            shown_at = Instant::now();
+            dirty = true;
+        }
+
+        let progress = shown_at.elapsed().as_secs_f32() / TRANSITION_TIME.as_secs_f32();
+        if previous.is_some() && progress >= 1.0 {
+            // The transition is over, the final frame still has to be drawn
+            previous = None;
+            dirty = true;
+        }
+        let blend = previous
+            .filter(|_| !matches!(transition, Transition::None))
+            .map(|previous| (&slides[previous], progress));
+
+        if dirty || blend.is_some() {
+            let mut frame = Vec::new();
+            write!(frame, "{HOME}")?;
+            for y in 0..size.height {
+                for x in 0..size.width {
+                    let cell = match blend {
+                        Some((from, progress)) => {
+                            let column = x as f32 / size.width as f32;
+                            let from = cell_at(from, x, y);
+                            let to = cell_at(&slides[current], x, y);
+                            mix(from, to, progress, column, transition)
+                        }
+                        None => cell_at(&slides[current], x, y),
+                    };
+                    match cell {
+                        Some(cell) => cell.write(&mut frame, mode)?,
+                        None if mode == ColorMode::None => write!(frame, " ")?,
+                        None => write!(frame, "{RESET} ")?,
+                    }
+                }
+                if mode == ColorMode::None {
+                    writeln!(frame, "{CLEAR_LINE}")?;
+                } else {
+                    writeln!(frame, "{RESET}{CLEAR_LINE}")?;
+                }
+            }
+
+            let name = flags[current].name();
+            let state = if paused { " (paused)" } else { "" };
+            write!(
+                frame,
+                "{name}{state}  ←/→ switch, space pause, q quit{CLEAR_LINE}"
+            )?;
+
+            out.write_all(&frame)?;
+            out.flush()?;
+            dirty = false;
+        }
+
+        thread::sleep(FRAME_TIME);
+    }
+
+    Ok(())
+}
+
+fn cell_at(cells: &[Vec<Cell>], x: usize, y: usize) -> Option<Cell> {
+    cells.get(y)?.get(x).copied()
+}
+
+/// The cell during a transition from `from` to `to`, `progress` goes from 0 to 1 and `column` is
+/// the horizontal position of the cell from 0 to 1
+fn mix(
+    from: Option<Cell>,
+    to: Option<Cell>,
+    progress: f32,
+    column: f32,
+    transition: Transition,
+) -> Option<Cell> {
+    match (transition, from, to) {
+        (Transition::None, _, to) => to,
+        (Transition::Wipe, from, to) => {
+            if column < progress {
+                to
+            } else {
+                from
+            }
+        }
+        (Transition::Fade, Some(from), Some(to)) => {
+            let blend =
+                |a: Paint, b: Paint| Paint::Overlay(a.true_color().mix(b.true_color(), progress));
+            Some(Cell {
+                char: if progress < 0.5 { from.char } else { to.char },
+                fg: blend(from.fg, to.fg),
+                bg: blend(from.bg, to.bg),
+            })
+        }
+        // Cells which are only part of one flag can't be blended
+        (Transition::Fade, from, to) => {
+            if progress < 0.5 {
+                from
+            } else {
+                to
+            }
+        }
+    }
+}
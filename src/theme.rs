@@ -0,0 +1,226 @@
+//! Terminal color schemes made from the colors of a flag.
+
+use crate::{
+    color::{AnsiColor, Color},
+    flag::Flag,
+};
+use clap::ValueEnum;
+use std::io;
+
+/// The contrast ratio between the fore- and background, 7 is the WCAG level for enhanced contrast
+const MIN_CONTRAST: f32 = 7.0;
+/// How much darker than the darkest stripe the background is
+const BACKGROUND_DARKENING: f32 = 0.85;
+/// How much lighter the bright colors are than the regular ones
+const BRIGHTENING: f32 = 0.3;
+
+const NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum Target {
+    /// TOML for the `[colors]` section of alacritty.toml
+    Alacritty,
+    /// A kitty.conf snippet
+    Kitty,
+    /// A TOML color scheme for the colors directory of WezTerm
+    Wezterm,
+    /// The `[colors]` section of foot.ini
+    Foot,
+    /// Resources for xterm, urxvt and friends
+    Xresources,
+    /// A scheme for the `schemes` list of the Windows Terminal settings
+    WindowsTerminalJson,
+}
+
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    pub foreground: Color,
+    /// The 8 regular colors followed by their 8 bright versions
+    pub ansi: [Color; 16],
+}
+
+impl Theme {
+    /// Maps the stripes of `flag` onto the ANSI colors they fall back to, the other basic colors
+    /// keep their xterm colors and the other bright ones are lightened basic colors.
+    ///
+    /// If multiple stripes fall back to the same color, the first one wins. The background is a
+    /// darkened version of the darkest stripe and the foreground the lightest stripe, lightened
+    /// until it is readable on the background.
+    pub fn new(flag: Flag) -> Self {
+        let stripes = flag.stripes();
+
+        let mut slots: [Option<Color>; 16] = [None; 16];
+        for stripe in &stripes {
+            let slot = &mut slots[stripe.ansi_color().index()];
+            if slot.is_none() {
+                *slot = Some(stripe.true_color());
+            }
+        }
+
+        let mut ansi = [Color::BLACK; 16];
+        for i in 0..8 {
+            ansi[i] = slots[i].unwrap_or(AnsiColor::ALL[i].rgb());
+            ansi[i + 8] = slots[i + 8].unwrap_or(ansi[i].mix(Color::WHITE, BRIGHTENING));
+        }
+
+        let by_luminance = |a: &Color, b: &Color| a.luminance().total_cmp(&b.luminance());
+        let colors = stripes.iter().map(|stripe| stripe.true_color());
+        let darkest = colors.clone().min_by(by_luminance).unwrap_or(Color::BLACK);
+        let lightest = colors.max_by(by_luminance).unwrap_or(Color::WHITE);
+
+        let background = darkest.mix(Color::BLACK, BACKGROUND_DARKENING);
+        let foreground = readable(lightest, background);
+
+        Self {
+            name: flag.name(),
+            background,
+            foreground,
+            ansi,
+        }
+    }
+
+    /// Writes the theme in the format of `target`
+    pub fn write(&self, out: &mut impl io::Write, target: Target) -> io::Result<()> {
+        let hex = |color: Color| color.hex();
+        let (regular, bright) = self.ansi.split_at(8);
+
+        match target {
+            Target::Alacritty => {
+                writeln!(out, "# {}", self.name)?;
+                writeln!(out, "[colors.primary]")?;
+                writeln!(out, "background = \"{}\"", hex(self.background))?;
+                writeln!(out, "foreground = \"{}\"", hex(self.foreground))?;
+                for (section, colors) in [("normal", regular), ("bright", bright)] {
+                    writeln!(out, "\n[colors.{section}]")?;
+                    for (name, &color) in NAMES.iter().zip(colors) {
+                        writeln!(out, "{name} = \"{}\"", hex(color))?;
+                    }
+                }
+            }
+            Target::Kitty => {
+                writeln!(out, "# {}", self.name)?;
+                writeln!(out, "background {}", hex(self.background))?;
+                writeln!(out, "foreground {}", hex(self.foreground))?;
+                writeln!(out, "cursor {}", hex(self.foreground))?;
+                for (i, &color) in self.ansi.iter().enumerate() {
+                    writeln!(out, "color{i} {}", hex(color))?;
+                }
+            }
+            Target::Wezterm => {
+                let list = |colors: &[Color]| {
+                    let colors = colors
+                        .iter()
+                        .map(|&color| format!("\"{}\"", hex(color)))
+                        .collect::<Vec<_>>();
+                    format!("[{}]", colors.join(", "))
+                };
+                writeln!(out, "[colors]")?;
+                writeln!(out, "background = \"{}\"", hex(self.background))?;
+                writeln!(out, "foreground = \"{}\"", hex(self.foreground))?;
+                writeln!(out, "cursor_bg = \"{}\"", hex(self.foreground))?;
+                writeln!(out, "ansi = {}", list(regular))?;
+                writeln!(out, "brights = {}", list(bright))?;
+                writeln!(out, "\n[metadata]")?;
+                writeln!(out, "name = \"{}\"", self.name)?;
+            }
+            Target::Foot => {
+                // Foot wants the colors without a leading `#`
+                let hex = |color: Color| color.hex().split_off(1);
+                writeln!(out, "# {}", self.name)?;
+                writeln!(out, "[colors]")?;
+                writeln!(out, "background={}", hex(self.background))?;
+                writeln!(out, "foreground={}", hex(self.foreground))?;
+                for (i, &color) in regular.iter().enumerate() {
+                    writeln!(out, "regular{i}={}", hex(color))?;
+                }
+                for (i, &color) in bright.iter().enumerate() {
+                    writeln!(out, "bright{i}={}", hex(color))?;
+                }
+            }
+            Target::Xresources => {
+                writeln!(out, "! {}", self.name)?;
+                writeln!(out, "*.background: {}", hex(self.background))?;
+                writeln!(out, "*.foreground: {}", hex(self.foreground))?;
+                writeln!(out, "*.cursorColor: {}", hex(self.foreground))?;
+                for (i, &color) in self.ansi.iter().enumerate() {
+                    writeln!(out, "*.color{i}: {}", hex(color))?;
+                }
+            }
+            Target::WindowsTerminalJson => {
+                let mut scheme = serde_json::Map::new();
+                let mut insert = |key: String, color: Color| {
+                    scheme.insert(key, serde_json::Value::String(hex(color).to_uppercase()));
+                };
+                insert(String::from("background"), self.background);
+                insert(String::from("foreground"), self.foreground);
+                insert(String::from("cursorColor"), self.foreground);
+                insert(String::from("selectionBackground"), self.foreground);
+                for (i, &color) in regular.iter().enumerate() {
+                    insert(windows_terminal_name(i), color);
+                }
+                for (i, &color) in bright.iter().enumerate() {
+                    let name = windows_terminal_name(i);
+                    let mut chars = name.chars();
+                    let capitalized = chars
+                        .next()
+                        .map(|c| c.to_ascii_uppercase())
+                        .into_iter()
+                        .chain(chars)
+                        .collect::<String>();
+                    insert(format!("bright{capitalized}"), color);
+                }
+                scheme.insert(
+                    String::from("name"),
+                    serde_json::Value::String(self.name.to_owned()),
+                );
+
+                serde_json::to_writer_pretty(&mut *out, &scheme)?;
+                writeln!(out)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Windows Terminal calls magenta purple
+fn windows_terminal_name(slot: usize) -> String {
+    match NAMES[slot] {
+        "magenta" => String::from("purple"),
+        name => name.to_owned(),
+    }
+}
+
+/// Moves `color` towards black or white, whichever contrasts more with `background`, until the
+/// contrast is at least [`MIN_CONTRAST`]
+fn readable(color: Color, background: Color) -> Color {
+    let target = if Color::WHITE.contrast(background) > Color::BLACK.contrast(background) {
+        Color::WHITE
+    } else {
+        Color::BLACK
+    };
+
+    (0..=10)
+        .map(|step| color.mix(target, step as f32 / 10.0))
+        .find(|color| color.contrast(background) >= MIN_CONTRAST)
+        .unwrap_or(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme() {
+        let theme = Theme::new(Flag::Trans);
+        assert!(theme.foreground.contrast(theme.background) >= MIN_CONTRAST);
+        // The light blue stripe falls back to cyan, the pink one to magenta
+        assert_eq!(theme.ansi[6].hex(), "#73cff4");
+        assert_eq!(theme.ansi[5].hex(), "#eeafc0");
+        // Nothing falls back to red
+        assert_eq!(theme.ansi[1].hex(), "#cd0000");
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    overlay::Size,
+    screen::{CLEAR, HOME, Key, Screen},
+};
+use signal_hook::consts::SIGWINCH;
+use std::{
+    io::{self, Write},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+/// How often keys and resizes are checked
+const POLL_TIME: Duration = Duration::from_millis(50);
+
+/// Shows a frame in the alternate screen and draws it again whenever the terminal is resized,
+/// until `q` is pressed or `stop` is set.
+///
+/// `size` is asked for the size of the flag before every redraw and `render` draws it.
+pub fn run(
+    mut size: impl FnMut() -> Size,
+    mut render: impl FnMut(&mut Vec<u8>, Size) -> io::Result<()>,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let resized = Arc::new(AtomicBool::new(true));
+    let signal = signal_hook::flag::register(SIGWINCH, Arc::clone(&resized))?;
+
+    let result = (|| {
+        let mut screen = Screen::enter()?;
+        let mut out = io::stdout().lock();
+
+        while !stop.load(Ordering::Relaxed) {
+            if screen
+                .keys()?
+                .iter()
+                .any(|key| matches!(key, Key::Char('q') | Key::Escape))
+            {
+                break;
+            }
+
+            if resized.swap(false, Ordering::Relaxed) {
+                let mut frame = Vec::new();
+                write!(frame, "{CLEAR}{HOME}")?;
+                render(&mut frame, size())?;
+                // A newline after the last row would scroll a flag as high as the terminal
+                if frame.ends_with(b"\n") {
+                    frame.pop();
+                }
+                out.write_all(&frame)?;
+                out.flush()?;
+            }
+
+            thread::sleep(POLL_TIME);
+        }
+
+        Ok(())
+    })();
+
+    signal_hook::low_level::unregister(signal);
+    result
+}